@@ -5,6 +5,158 @@ pub fn parse_opts() -> ArgMatches<'static> {
         .version("0.1.0")
         .author("anonymous")
         .subcommand(SubCommand::with_name("compute-secure-announcements"))
+        .subcommand(
+            SubCommand::with_name("plan")
+                .about("solve a MAPF instance and write the solution YAML")
+                .arg(
+                    Arg::with_name("mapf-instance")
+                        .required(true)
+                        .takes_value(true)
+                        .short("m")
+                        .long("mapf-instance")
+                        .display_order(0)
+                        .help("path to instance YAML"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .required(true)
+                        .takes_value(true)
+                        .short("o")
+                        .long("output")
+                        .display_order(1)
+                        .help("path to output solution YAML"),
+                )
+                .arg(
+                    Arg::with_name("planner")
+                        .takes_value(true)
+                        .short("p")
+                        .long("planner")
+                        .possible_values(&["prioritized", "cbs"])
+                        .default_value("prioritized")
+                        .display_order(2)
+                        .help("MAPF algorithm to use"),
+                )
+                .arg(
+                    Arg::with_name("max-inter-observation")
+                        .takes_value(true)
+                        .short("b")
+                        .long("max-inter-observation")
+                        .validator(is_usize)
+                        .help("repair the plan so agents are co-observed at least this often"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("check-plan")
+                .about("list every way a solution violates its instance")
+                .arg(
+                    Arg::with_name("mapf-instance")
+                        .required(true)
+                        .takes_value(true)
+                        .short("m")
+                        .long("mapf-instance")
+                        .display_order(0)
+                        .help("path to instance YAML"),
+                )
+                .arg(
+                    Arg::with_name("mapf-solution")
+                        .required(true)
+                        .takes_value(true)
+                        .short("s")
+                        .long("mapf-solution")
+                        .display_order(1)
+                        .help("path to solution YAML corres. to <mapf-instance>"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("repair-plan")
+                .about("delay or reroute agents to bound their max inter-observation time")
+                .arg(
+                    Arg::with_name("mapf-instance")
+                        .required(true)
+                        .takes_value(true)
+                        .short("m")
+                        .long("mapf-instance")
+                        .display_order(0)
+                        .help("path to instance YAML"),
+                )
+                .arg(
+                    Arg::with_name("mapf-solution")
+                        .required(true)
+                        .takes_value(true)
+                        .short("s")
+                        .long("mapf-solution")
+                        .display_order(1)
+                        .help("path to solution YAML corres. to <mapf-instance>"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .required(true)
+                        .takes_value(true)
+                        .short("o")
+                        .long("output")
+                        .display_order(2)
+                        .help("path to output solution YAML"),
+                )
+                .arg(
+                    Arg::with_name("max-inter-observation")
+                        .required(true)
+                        .takes_value(true)
+                        .short("b")
+                        .long("max-inter-observation")
+                        .validator(is_usize)
+                        .display_order(3)
+                        .help("longest tolerated gap between co-observations of an agent"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("window-stream")
+                .about("cut a stream of plan segments into windows of fixed length to analyze like finite plans")
+                .arg(
+                    Arg::with_name("mapf-instance")
+                        .required(true)
+                        .takes_value(true)
+                        .short("m")
+                        .long("mapf-instance")
+                        .display_order(0)
+                        .help("path to instance YAML"),
+                )
+                .arg(
+                    Arg::with_name("segments")
+                        .required(true)
+                        .takes_value(true)
+                        .short("s")
+                        .long("segments")
+                        .display_order(1)
+                        .help("path to the plan segments, one JSON object per line in the order they were issued"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .required(true)
+                        .takes_value(true)
+                        .short("o")
+                        .long("output")
+                        .display_order(2)
+                        .help("directory to write maps/, plans/ and instances.txt to, as a suite manifest reads them"),
+                )
+                .arg(
+                    Arg::with_name("window")
+                        .required(true)
+                        .takes_value(true)
+                        .short("w")
+                        .long("window")
+                        .validator(is_positive)
+                        .display_order(3)
+                        .help("time steps each window covers, its makespan"),
+                )
+                .arg(
+                    Arg::with_name("stride")
+                        .takes_value(true)
+                        .long("stride")
+                        .validator(is_positive)
+                        .display_order(4)
+                        .help("time steps between the starts of consecutive windows [default: <window>]"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("generate-plots")
                 .about("read list of .yaml output files from stdin and generate plots")
@@ -18,22 +170,104 @@ pub fn parse_opts() -> ArgMatches<'static> {
                     Arg::with_name("plot")
                         .required(true)
                         .index(1)
-                        .possible_values(&[
-                            "succ-vs-kahead",
-                            "succ-vs-max-inter-obs",
-                            "succ-vs-kgrouped",
-                            "succ-vs-robust",
-                            "secure-vs-kahead",
-                            "secure-vs-max-inter-obs",
-                            "secure-vs-kgrouped",
-                            "secure-vs-robust",
-                        ]),
+                        .help(
+                            "built-in plot (succ-vs-kahead, succ-vs-max-inter-obs, \
+                             succ-vs-kgrouped, succ-vs-robust, succ-vs-kahead-ci, succ-vs-kahead-box, \
+                             secure-vs-kahead, secure-vs-max-inter-obs, secure-vs-kgrouped, \
+                             secure-vs-robust, secure-vs-kahead-ci) \
+                             or path to a .yaml plot spec",
+                        ),
                 )
                 .arg(
                     Arg::with_name("force")
                         .short("f")
                         .help("overwrite existing plots"),
                 ),
+        ).subcommand(
+            SubCommand::with_name("generate-heatmap")
+                .about("read list of .yaml output files from stdin and colour each target cell of the map")
+                .arg(
+                    Arg::with_name("output")
+                        .required(true)
+                        .index(1)
+                        .help("path to output file"),
+                )
+                .arg(
+                    Arg::with_name("mapf-instance")
+                        .required(true)
+                        .takes_value(true)
+                        .short("m")
+                        .long("mapf-instance")
+                        .help("path to instance YAML the results were computed on"),
+                )
+                .arg(
+                    Arg::with_name("mapf-solution")
+                        .takes_value(true)
+                        .short("s")
+                        .long("mapf-solution")
+                        .help("path to solution YAML, overlays its nominal paths"),
+                )
+                .arg(
+                    Arg::with_name("type")
+                        .required(true)
+                        .takes_value(true)
+                        .short("t")
+                        .long("type")
+                        .help("the attacker type of the results")
+                        .possible_values(&["bold", "cautious"]),
+                )
+                .arg(
+                    Arg::with_name("metric")
+                        .takes_value(true)
+                        .long("metric")
+                        .help("attempt field to average per cell, defaults to dangerous (bold) or secured (cautious)"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .help("overwrite existing plots"),
+                ),
+        ).subcommand(
+            SubCommand::with_name("summarize")
+                .about("read list of .yaml output files from stdin and flatten them into one table")
+                .arg(
+                    Arg::with_name("output")
+                        .required(true)
+                        .index(1)
+                        .help("path to output .csv, or .parquet if built with the parquet feature"),
+                )
+                .arg(
+                    Arg::with_name("type")
+                        .required(true)
+                        .takes_value(true)
+                        .short("t")
+                        .long("type")
+                        .help("the attacker type of the results")
+                        .possible_values(&["bold", "cautious"]),
+                )
+                .arg(
+                    Arg::with_name("level")
+                        .takes_value(true)
+                        .short("l")
+                        .long("level")
+                        .possible_values(&["experiment", "attempt"])
+                        .default_value("experiment")
+                        .help("one row per result file or per attempt"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .help("overwrite an existing table"),
+                ),
+        ).subcommand(
+            SubCommand::with_name("run-suite")
+                .about("run every analyze-attackers job of a manifest, skipping finished ones")
+                .arg(
+                    Arg::with_name("manifest")
+                        .required(true)
+                        .index(1)
+                        .help("path to suite manifest YAML"),
+                ),
         ).subcommand(
             SubCommand::with_name("analyze-attackers")
                 .arg(
@@ -66,16 +300,7 @@ pub fn parse_opts() -> ArgMatches<'static> {
                         .display_order(1)
                         .help("path to solution YAML corres. to <mapf-instance>"),
                 )
-                .arg(
-                    Arg::with_name("announcement-strategy")
-                        .required(true)
-                        .takes_value(true)
-                        .short("a")
-                        .long("announcement-strategy")
-                        .possible_values(&["kahead", "kgrouped", "robust", "custom"])
-                        .display_order(2)
-                        .help("announcement strategy to use"),
-                )
+                .args(&announcement_args())
                 .arg(
                     Arg::with_name("output")
                         .required(true)
@@ -86,26 +311,214 @@ pub fn parse_opts() -> ArgMatches<'static> {
                         .help("path to output file"),
                 )
                 .arg(
-                    Arg::with_name("lookahead")
+                    Arg::with_name("skip-large")
+                        .short("x")
+                        .long("skip-large")
+                        .help("if provided lookahead is larger than makespan, do nothing"),
+                )
+                .arg(
+                    Arg::with_name("no-mitigation")
+                    .short("n")
+                    .long("no-mitigation")
+                    .help("disable detections due to incorrect co-observations"),
+                )
+                .arg(
+                    Arg::with_name("resume")
+                        .short("r")
+                        .long("resume")
+                        .help("continue an interrupted run from <output>.partial.jsonl, skipping the attempts already there"),
+                )
+                .arg(
+                    Arg::with_name("no-wall-time")
+                        .long("no-wall-time")
+                        .help("leave the wall time out of the provenance header, so reruns on the same inputs write identical files"),
+                )
+                .arg(
+                    Arg::with_name("attempt-timeout")
                         .takes_value(true)
-                        .short("k")
-                        .long("lookahead")
-                        .required_ifs(&[("announcement-strategy", "kahead"),("announcement_strategy","kgrouped")])
-                        .help("if using kahead strategy; fixed lookahead. if using kgrouped strategy; k grouping."),
+                        .long("attempt-timeout")
+                        .value_name("SECONDS")
+                        .validator(is_usize)
+                        .help("give up on a cautious attempt after this long, recording it as unknown"),
                 )
                 .arg(
-                    Arg::with_name("custom-announcements")
+                    Arg::with_name("attempt-iterations")
                         .takes_value(true)
-                        .short("c")
-                        .long("custom-announcements")
-                        .required_if("announcement-strategy", "custom")
-                        .help("if using custom strategy, path to custom lookaheads YAML"),
+                        .long("attempt-iterations")
+                        .validator(is_usize)
+                        .help("give up on a cautious attempt after this many iterations of its fixpoint loop, recording it as unknown"),
                 )
                 .arg(
-                    Arg::with_name("skip-large")
-                        .short("x")
-                        .long("skip-large")
-                        .help("if provided lookahead is larger than makespan, do nothing"),
+                    Arg::with_name("detection-probability")
+                        .takes_value(true)
+                        .long("detection-probability")
+                        .validator(is_probability)
+                        .conflicts_with("no-mitigation")
+                        .help("chance a defender notices a deviation it co-observes, bold attackers only; estimates miss rates over --sensing-runs runs [default: 1]"),
+                )
+                .arg(
+                    Arg::with_name("false-positive-rate")
+                        .takes_value(true)
+                        .long("false-positive-rate")
+                        .validator(is_probability)
+                        .conflicts_with("no-mitigation")
+                        .help("chance a defender raises an alarm on a co-observation matching the plan, bold attackers only [default: 0]"),
+                )
+                .arg(
+                    Arg::with_name("liars")
+                        .takes_value(true)
+                        .long("liars")
+                        .validator(is_usize)
+                        .conflicts_with("no-mitigation")
+                        .help("defenders drawn anew every run to misreport their co-observations as --liar-behavior says, bold attackers only [default: 0]"),
+                )
+                .arg(
+                    Arg::with_name("liar-behavior")
+                        .takes_value(true)
+                        .long("liar-behavior")
+                        .possible_values(&["omit", "fabricate", "vouch"])
+                        .conflicts_with("no-mitigation")
+                        .help("what liars report: nothing, accusations regardless, or that the attacker follows its plan [default: omit]"),
+                )
+                .arg(
+                    Arg::with_name("quorum")
+                        .takes_value(true)
+                        .long("quorum")
+                        .validator(is_positive)
+                        .conflicts_with("no-mitigation")
+                        .help("distinct defenders that must accuse the attacker to raise an alarm, bold attackers only [default: 1]"),
+                )
+                .arg(
+                    Arg::with_name("sensing-runs")
+                        .takes_value(true)
+                        .long("sensing-runs")
+                        .default_value("1000")
+                        .validator(is_usize)
+                        .help("Monte Carlo runs per attempt with any of --detection-probability, --false-positive-rate, --liars, --liar-behavior or --quorum"),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .takes_value(true)
+                        .long("seed")
+                        .default_value("0")
+                        .validator(is_usize)
+                        .help("seed of the Monte Carlo runs and of the broadcasts --drop-probability loses"),
+                )
+                .arg(
+                    Arg::with_name("confidence")
+                        .takes_value(true)
+                        .long("confidence")
+                        .default_value("0.95")
+                        .validator(is_confidence)
+                        .help("confidence of the intervals around the estimated miss and false alarm rates"),
+                )
+                .arg(
+                    Arg::with_name("drop-probability")
+                        .takes_value(true)
+                        .long("drop-probability")
+                        .validator(is_probability)
+                        .help("chance an announcement broadcast is lost on each link, so agents know less of each other's plans [default: 0]"),
+                )
+                .arg(
+                    Arg::with_name("latency")
+                        .takes_value(true)
+                        .long("latency")
+                        .validator(is_usize)
+                        .help("time steps an announcement broadcast takes to arrive [default: 0]"),
+                )
+                .arg(
+                    Arg::with_name("range")
+                        .takes_value(true)
+                        .long("range")
+                        .validator(is_usize)
+                        .help("Manhattan distance an announcement broadcast reaches [default: unlimited]"),
+                )
+                .arg(
+                    Arg::with_name("equivocate")
+                        .long("equivocate")
+                        .conflicts_with_all(&["no-mitigation", "detection-probability", "false-positive-rate", "liars", "liar-behavior", "quorum"])
+                        .help("bold attackers tell their deviation to the defenders that would notice it and their plan to the rest"),
+                )
+                .arg(
+                    Arg::with_name("no-cross-checks")
+                        .long("no-cross-checks")
+                        .requires("equivocate")
+                        .help("defenders that co-observe each other do not compare what an equivocating attacker told them"),
+                )
+                .arg(
+                    Arg::with_name("responders")
+                        .takes_value(true)
+                        .long("responders")
+                        .validator(is_positive)
+                        .conflicts_with_all(&["equivocate", "detection-probability", "false-positive-rate", "liars", "liar-behavior", "quorum"])
+                        .help("defenders nearest a detected bold attacker that go after it, to tell whether it would have been stopped before the target"),
+                )
+                .arg(
+                    Arg::with_name("security-robot")
+                        .takes_value(true)
+                        .long("security-robot")
+                        .conflicts_with_all(&["responders", "equivocate", "detection-probability", "false-positive-rate", "liars", "liar-behavior", "quorum"])
+                        .help("agent that goes after a detected bold attacker alone, instead of the nearest defenders"),
+                )
+                .arg(
+                    Arg::with_name("response")
+                        .takes_value(true)
+                        .long("response")
+                        .possible_values(&["intercept", "cordon"])
+                        .help("whether responders head for the attacker or hold a cell next to its target, with --responders or --security-robot [default: intercept]"),
+                )
+        ).subcommand(
+            SubCommand::with_name("replay")
+                .about("animate one bold attempt: nominal paths, announced horizons, observations and detections")
+                .arg(
+                    Arg::with_name("mapf-instance")
+                        .required(true)
+                        .takes_value(true)
+                        .short("m")
+                        .long("mapf-instance")
+                        .display_order(0)
+                        .help("path to instance YAML"),
+                )
+                .arg(
+                    Arg::with_name("mapf-solution")
+                        .required(true)
+                        .takes_value(true)
+                        .short("s")
+                        .long("mapf-solution")
+                        .display_order(1)
+                        .help("path to solution YAML corres. to <mapf-instance>"),
+                )
+                .args(&announcement_args())
+                .arg(
+                    Arg::with_name("attacker")
+                        .required(true)
+                        .takes_value(true)
+                        .long("attacker")
+                        .help("name of the attacking agent"),
+                )
+                .arg(
+                    Arg::with_name("target")
+                        .required(true)
+                        .takes_value(true)
+                        .long("target")
+                        .validator(is_coordinate)
+                        .help("obstacle cell the attacker heads for, as x,y"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .required(true)
+                        .takes_value(true)
+                        .short("o")
+                        .long("output")
+                        .help("animated .gif, or a directory to write one .svg per time step into"),
+                )
+                .arg(
+                    Arg::with_name("delay")
+                        .takes_value(true)
+                        .long("delay")
+                        .default_value("500")
+                        .validator(is_usize)
+                        .help("milliseconds per frame of the .gif"),
                 )
                 .arg(
                     Arg::with_name("no-mitigation")
@@ -113,5 +526,115 @@ pub fn parse_opts() -> ArgMatches<'static> {
                     .long("no-mitigation")
                     .help("disable detections due to incorrect co-observations"),
                 )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .help("overwrite an existing replay"),
+                )
+        ).subcommand(
+            SubCommand::with_name("inspect-cautious")
+                .about("step through the floods of cautious attempts on one target in the terminal")
+                .arg(
+                    Arg::with_name("mapf-instance")
+                        .required(true)
+                        .takes_value(true)
+                        .short("m")
+                        .long("mapf-instance")
+                        .display_order(0)
+                        .help("path to instance YAML"),
+                )
+                .arg(
+                    Arg::with_name("mapf-solution")
+                        .required(true)
+                        .takes_value(true)
+                        .short("s")
+                        .long("mapf-solution")
+                        .display_order(1)
+                        .help("path to solution YAML corres. to <mapf-instance>"),
+                )
+                .args(&announcement_args())
+                .arg(
+                    Arg::with_name("attacker")
+                        .takes_value(true)
+                        .long("attacker")
+                        .help("agent to start with, the first one if not given"),
+                )
+                .arg(
+                    Arg::with_name("target")
+                        .required(true)
+                        .takes_value(true)
+                        .long("target")
+                        .validator(is_coordinate)
+                        .help("obstacle cell the attackers head for, as x,y"),
+                )
         ).get_matches()
 }
+
+// -a, -k and -c, shared by every subcommand that computes announcements
+fn announcement_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("announcement-strategy")
+            .required(true)
+            .takes_value(true)
+            .short("a")
+            .long("announcement-strategy")
+            .possible_values(&["kahead", "kgrouped", "robust", "custom"])
+            .display_order(2)
+            .help("announcement strategy to use"),
+        Arg::with_name("lookahead")
+            .takes_value(true)
+            .short("k")
+            .long("lookahead")
+            .required_ifs(&[("announcement-strategy", "kahead"), ("announcement-strategy", "kgrouped")])
+            .validator(is_usize)
+            .help("if using kahead strategy; fixed lookahead. if using kgrouped strategy; k grouping."),
+        Arg::with_name("custom-announcements")
+            .takes_value(true)
+            .short("c")
+            .long("custom-announcements")
+            .required_if("announcement-strategy", "custom")
+            .help("if using custom strategy, path to custom lookaheads YAML"),
+    ]
+}
+
+fn is_usize(value: String) -> Result<(), String> {
+    value
+        .parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| format!("expected a non-negative integer, got {}", value))
+}
+
+fn is_positive(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(format!("expected a positive integer, got {}", value)),
+    }
+}
+
+fn is_probability(value: String) -> Result<(), String> {
+    match value.parse::<f64>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(()),
+        _ => Err(format!(
+            "expected a probability between 0 and 1, got {}",
+            value
+        )),
+    }
+}
+
+fn is_confidence(value: String) -> Result<(), String> {
+    match value.parse::<f64>() {
+        Ok(c) if c > 0.0 && c < 1.0 => Ok(()),
+        _ => Err(format!(
+            "expected a confidence strictly between 0 and 1, got {}",
+            value
+        )),
+    }
+}
+
+fn is_coordinate(value: String) -> Result<(), String> {
+    let parts: Vec<&str> = value.split(',').collect();
+    match parts.as_slice() {
+        [x, y] if x.trim().parse::<u16>().is_ok() && y.trim().parse::<u16>().is_ok() => Ok(()),
+        _ => Err(format!("expected a cell as x,y, got {}", value)),
+    }
+}
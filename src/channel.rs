@@ -0,0 +1,155 @@
+//! Lossy, delayed and range-limited delivery of announcements, so every agent holds its own view
+//! of how far the others' plans are known, see [`Channel`].
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::compiled::Compiled;
+
+/// How announcements travel between agents. Without one every announcement reaches every agent
+/// the moment it is made.
+///
+/// An agent broadcasts whenever its announced horizon grows. Each other agent within `range`
+/// of it at that time receives the broadcast `latency` time steps later, unless it is dropped,
+/// independently per link. A broadcast carries the whole announced prefix, so a later one makes
+/// up for a lost one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Channel {
+    /// Chance a broadcast is lost on one link.
+    pub drop_probability: f64,
+    /// Time steps between a broadcast and its arrival.
+    #[serde(default)]
+    pub latency: usize,
+    /// Manhattan distance a broadcast reaches, unlimited if absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<usize>,
+    /// Seeds the drops, so reruns and resumed runs lose the same broadcasts.
+    pub seed: u64,
+}
+
+impl Channel {
+    /// For every listener, speaker and time step, in that order of nesting, the first time step
+    /// of the speaker's plan the listener has not heard of. Agents always know their own
+    /// announcements.
+    pub fn knowledge(&self, compiled: &Compiled) -> Vec<usize> {
+        let agents = compiled.agent_count();
+        let steps = compiled.announced_len();
+        let position = |agent, t: usize| compiled.at(agent, t.min(compiled.makespan + 1));
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut knowledge = vec![0; agents * agents * steps];
+        for t in 0..steps {
+            for speaker in compiled.agents() {
+                let horizon = compiled.announced(speaker, t);
+                let s = speaker as usize;
+                knowledge[(s * agents + s) * steps + t] = horizon;
+                if t > 0 && horizon <= compiled.announced(speaker, t - 1) {
+                    continue;
+                }
+                for listener in compiled.defenders(speaker) {
+                    let distance = position(speaker, t).manh_dist(&position(listener, t));
+                    let in_range = match self.range {
+                        Some(range) => distance <= range,
+                        None => true,
+                    };
+                    if !in_range || rng.gen::<f64>() < self.drop_probability {
+                        continue;
+                    }
+                    let row = (listener as usize * agents + s) * steps;
+                    for known in &mut knowledge[row + (t + self.latency).min(steps)..row + steps] {
+                        *known = (*known).max(horizon);
+                    }
+                }
+            }
+        }
+        knowledge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::{run_cautious_analysis, CautiousBudget};
+    use crate::inputs::{Announcements, MapfInstance, MapfSolution};
+    use crate::planner::plan_prioritized;
+    use crate::utils::{compute_kahead_announcements, extend_stay_in_place};
+
+    const INSTANCE: &str = "
+map:
+  dimensions: [10, 8]
+  obstacles: [[4, 3], [5, 3], [4, 4]]
+agents:
+  - {name: agent0, start: [1, 1], goal: [8, 6]}
+  - {name: agent1, start: [8, 1], goal: [1, 6]}
+  - {name: agent2, start: [1, 6], goal: [8, 2]}
+";
+
+    fn inputs() -> (MapfInstance, MapfSolution, Announcements) {
+        let instance: MapfInstance = serde_yaml::from_str(INSTANCE).unwrap();
+        let mut solution = plan_prioritized(&instance).unwrap();
+        extend_stay_in_place(&mut solution);
+        let announcements =
+            compute_kahead_announcements(&instance.agents, 3, solution.statistics.makespan);
+        (instance, solution, announcements)
+    }
+
+    fn compiled() -> Compiled {
+        let (instance, solution, announcements) = inputs();
+        Compiled::new(&instance, &solution, &announcements)
+    }
+
+    #[test]
+    fn listeners_fall_behind_by_the_latency_and_the_lost_broadcasts() {
+        let perfect = compiled();
+        let channel = |drop_probability, latency, range| Channel {
+            drop_probability,
+            latency,
+            range,
+            seed: 7,
+        };
+        let steps = perfect.announced_len();
+        let reliable = perfect.clone().with_channel(&channel(0.0, 0, None));
+        for t in 0..steps {
+            assert_eq!(reliable.known(0, 1, t), perfect.announced(1, t));
+        }
+
+        let delayed = perfect.clone().with_channel(&channel(0.0, 2, None));
+        assert_eq!(delayed.known(0, 1, 1), 0);
+        assert_eq!(delayed.known(0, 1, 5), perfect.announced(1, 3));
+        assert_eq!(delayed.known(1, 1, 1), perfect.announced(1, 1));
+
+        // a lost broadcast leaves the listener behind until the next one arrives
+        let lossy = perfect.clone().with_channel(&channel(0.5, 0, None));
+        let mut behind = 0;
+        for t in 0..steps {
+            let known = lossy.known(0, 1, t);
+            assert!(known <= perfect.announced(1, t));
+            behind += (known < perfect.announced(1, t)) as usize;
+        }
+        assert!(0 < behind && behind < steps);
+
+        let deaf = perfect.with_channel(&channel(0.0, 0, Some(0)));
+        assert_eq!(deaf.known(0, 1, steps - 1), 0);
+        assert!(!deaf.knows_step(0, 1, 0));
+    }
+
+    #[test]
+    fn lost_broadcasts_are_the_same_on_a_rerun() {
+        let channel = Channel {
+            drop_probability: 0.05,
+            latency: 1,
+            range: None,
+            seed: 3,
+        };
+        assert_eq!(
+            compiled().with_channel(&channel),
+            compiled().with_channel(&channel)
+        );
+        let run = || {
+            let (instance, solution, announcements) = inputs();
+            let budget = CautiousBudget::default();
+            run_cautious_analysis(instance, solution, announcements, Some(&channel), &budget)
+        };
+        assert_eq!(run(), run());
+    }
+}
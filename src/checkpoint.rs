@@ -0,0 +1,217 @@
+//! Append-only logs of finished attempts, so an interrupted analysis can resume where it
+//! stopped, see [`Checkpoint`].
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+use crate::inputs::Coordinate;
+
+/// An attempt result, identified by its attacker and target.
+pub trait Attempt {
+    fn attacker_name(&self) -> &str;
+    fn safe(&self) -> &Coordinate;
+}
+
+/// The attempts an analysis has finished so far, one JSON object per line after a header line
+/// with the configuration they were run with. Every attempt is appended and flushed as soon as
+/// it finishes, so a killed run loses at most the attempts in flight.
+pub struct Checkpoint<T> {
+    path: String,
+    file: Mutex<File>,
+    done: Vec<T>,
+    keys: HashSet<(String, Coordinate)>,
+}
+
+impl<T: Attempt + Serialize + DeserializeOwned> Checkpoint<T> {
+    /// Starts an empty checkpoint at `path` for attempts run with `header`, replacing any
+    /// earlier one.
+    pub fn create(path: &str, header: &impl Serialize) -> Result<Checkpoint<T>> {
+        let file = File::create(path).map_err(|source| io_error(path, source))?;
+        let checkpoint = Checkpoint {
+            path: path.to_string(),
+            file: Mutex::new(file),
+            done: Vec::new(),
+            keys: HashSet::new(),
+        };
+        checkpoint.append(header)?;
+        Ok(checkpoint)
+    }
+
+    /// Reopens the checkpoint at `path` to append to it, or starts one if there is none. A last
+    /// line cut short by the interruption is dropped. Fails if the checkpoint was written with
+    /// another `header`, rather than mixing attempts run with different configurations.
+    pub fn resume(path: &str, header: &impl Serialize) -> Result<Checkpoint<T>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(|source| io_error(path, source))?;
+        let expected = json_line(path, header)?;
+        let mut done = Vec::new();
+        let mut reader = BufReader::new(&mut file);
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|source| io_error(path, source))?;
+        // a header cut short means no attempt was written yet
+        let headed = line.ends_with('\n');
+        if headed && line != expected {
+            return Err(Error::Inconsistent {
+                instance: path.to_string(),
+                other: "this run's configuration".to_string(),
+                reason: "the checkpoint was written by a run with another one, \
+                         start over without --resume"
+                    .to_string(),
+            });
+        }
+        let mut complete = 0;
+        if headed {
+            complete = line.len() as u64;
+            for number in 2.. {
+                line.clear();
+                let read = reader
+                    .read_line(&mut line)
+                    .map_err(|source| io_error(path, source))?;
+                if read == 0 {
+                    break;
+                }
+                match serde_json::from_str::<T>(&line) {
+                    Ok(attempt) => done.push(attempt),
+                    Err(_) if !line.ends_with('\n') => break,
+                    Err(why) => {
+                        let source = io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("line {}: {}", number, why),
+                        );
+                        return Err(io_error(path, source));
+                    }
+                }
+                complete += read as u64;
+            }
+        }
+        file.set_len(complete)
+            .map_err(|source| io_error(path, source))?;
+        let keys = done
+            .iter()
+            .map(|attempt: &T| (attempt.attacker_name().to_string(), *attempt.safe()))
+            .collect();
+        let checkpoint = Checkpoint {
+            path: path.to_string(),
+            file: Mutex::new(file),
+            done,
+            keys,
+        };
+        if !headed {
+            checkpoint.append(header)?;
+        }
+        Ok(checkpoint)
+    }
+
+    /// Number of attempts already in the checkpoint when it was opened.
+    pub fn len(&self) -> usize {
+        self.done.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.done.is_empty()
+    }
+
+    /// The attempts already in the checkpoint when it was opened.
+    pub fn into_done(self) -> Vec<T> {
+        self.done
+    }
+
+    pub fn contains(&self, attacker_name: &str, safe: &Coordinate) -> bool {
+        self.keys.contains(&(attacker_name.to_string(), *safe))
+    }
+
+    /// Appends `attempt` and flushes it to disk.
+    pub fn record(&self, attempt: &T) -> Result<()> {
+        self.append(attempt)
+    }
+
+    fn append(&self, value: &impl Serialize) -> Result<()> {
+        let line = json_line(&self.path, value)?;
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|source| io_error(&self.path, source))
+    }
+}
+
+fn json_line(path: &str, value: &impl Serialize) -> Result<String> {
+    let mut line = serde_json::to_string(value)
+        .map_err(|why| io_error(path, io::Error::new(io::ErrorKind::InvalidData, why)))?;
+    line.push('\n');
+    Ok(line)
+}
+
+fn io_error(path: &str, source: io::Error) -> Error {
+    Error::Io {
+        path: path.to_string(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::CautiousAttemptResult;
+    use std::fs;
+
+    #[test]
+    fn resume_keeps_finished_attempts_and_drops_a_cut_off_line() {
+        let attempt = |attacker_name: &str, x| CautiousAttemptResult {
+            attacker_name: attacker_name.to_string(),
+            safe: Coordinate { x, y: 0 },
+            max_inter_observation_time: 3,
+            min_inter_announcement_time: 1,
+            min_lookahead: 2,
+            avg_lookahead: None,
+            verdict: (x % 2 == 0).into(),
+        };
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let checkpoint = Checkpoint::create(path, &"k=2").unwrap();
+        checkpoint.record(&attempt("agent0", 1)).unwrap();
+        checkpoint.record(&attempt("agent1", 2)).unwrap();
+        drop(checkpoint);
+        // killed halfway through writing a third attempt
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(b"{\"attacker_name\":\"agent2\",\"sa")
+            .unwrap();
+        drop(file);
+
+        let checkpoint = Checkpoint::<CautiousAttemptResult>::resume(path, &"k=2").unwrap();
+        assert_eq!(checkpoint.len(), 2);
+        assert!(checkpoint.contains("agent1", &Coordinate { x: 2, y: 0 }));
+        assert!(!checkpoint.contains("agent1", &Coordinate { x: 1, y: 0 }));
+        assert!(!checkpoint.contains("agent2", &Coordinate { x: 3, y: 0 }));
+        checkpoint.record(&attempt("agent2", 3)).unwrap();
+        drop(checkpoint);
+        // attempts run with another k are not the same analysis
+        assert_eq!(
+            Checkpoint::<CautiousAttemptResult>::resume(path, &"k=3")
+                .err()
+                .map(|error| error.exit_code()),
+            Some(5)
+        );
+
+        let checkpoint = Checkpoint::<CautiousAttemptResult>::resume(path, &"k=2").unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(
+            checkpoint.into_done(),
+            vec![
+                attempt("agent0", 1),
+                attempt("agent1", 2),
+                attempt("agent2", 3)
+            ]
+        );
+    }
+}
@@ -0,0 +1,384 @@
+//! Hash-chain commitments to announced plans, so a horizon-limited announcement cannot silently
+//! rewrite what an agent committed to earlier, see [`commit`] and [`verify`].
+//!
+//! Every agent's plan is folded into a backward hash chain: the commitment to the steps from
+//! `s` on is `c_s = SHA-256(salt_s | x_s | y_s | s | c_{s+1})`, ending in [`TERMINAL`] after
+//! the last step. An agent publishes `c_0` before moving, then at every time step reveals the
+//! steps its [`Announcements`] newly announce with their salts, together with the commitment to
+//! the rest of its plan. The reveal only hashes back to the commitment published before it if
+//! the revealed steps are the ones committed to, and the salts keep unrevealed steps hidden.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::inputs::{Announcements, MapfSolution, TimedCoordinate};
+
+/// Commitment following the last step of a plan, all zeros.
+pub const TERMINAL: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One revealed step of a plan and the salt it was committed with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reveal {
+    pub step: TimedCoordinate,
+    pub salt: String,
+}
+
+/// What an agent publishes at time step `t`: the steps newly announced at `t` and the
+/// commitment to everything after them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Publication {
+    pub t: usize,
+    pub revealed: Vec<Reveal>,
+    pub commitment: String,
+}
+
+/// An agent's commitment to its whole plan, published before it moves, and its publications.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentTranscript {
+    pub head: String,
+    pub publications: Vec<Publication>,
+}
+
+/// Every agent's commitments and reveals, by agent name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub agents: BTreeMap<String, AgentTranscript>,
+}
+
+/// How a transcript breaks its commitments or announcements, see [`verify`].
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum CommitmentViolationKind {
+    MissingTranscript,
+    /// The publication is labelled with another time step.
+    OutOfOrder {
+        found: usize,
+    },
+    /// A revealed step is not the one following those revealed before.
+    Gap {
+        expected: usize,
+        found: usize,
+    },
+    /// Fewer steps were revealed than the announcements announce by then.
+    Withheld {
+        announced: usize,
+        revealed: usize,
+    },
+    /// The agent stopped publishing while its announcements go on.
+    Missing,
+    /// The reveal does not hash to the commitment published before it.
+    Broken,
+}
+
+/// A [`CommitmentViolationKind`] located at an agent and time step.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct CommitmentViolation {
+    pub agent: String,
+    pub t: usize,
+    pub kind: CommitmentViolationKind,
+}
+
+impl fmt::Display for CommitmentViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at t={}: ", self.agent, self.t)?;
+        match &self.kind {
+            CommitmentViolationKind::MissingTranscript => write!(f, "no transcript"),
+            CommitmentViolationKind::OutOfOrder { found } => {
+                write!(f, "publication labelled t={}", found)
+            }
+            CommitmentViolationKind::Gap { expected, found } => {
+                write!(f, "reveals step {} instead of {}", found, expected)
+            }
+            CommitmentViolationKind::Withheld {
+                announced,
+                revealed,
+            } => write!(
+                f,
+                "revealed up to step {} but announced up to {}",
+                revealed, announced
+            ),
+            CommitmentViolationKind::Missing => write!(f, "no publication from here on"),
+            CommitmentViolationKind::Broken => {
+                write!(f, "reveal does not match the earlier commitment")
+            }
+        }
+    }
+}
+
+/// The transcript of every agent of `solution` publishing as `announcements` say, with salts
+/// derived from `secret`. Steps past the end of a plan are never revealed, so `solution` should
+/// be padded with [`extend_stay_in_place`](crate::extend_stay_in_place) to cover the time steps
+/// the announcements do.
+pub fn commit(solution: &MapfSolution, announcements: &Announcements, secret: &[u8]) -> Transcript {
+    let agents = solution
+        .schedule
+        .iter()
+        .map(|(name, path)| {
+            let salts: Vec<String> = (0..path.len()).map(|s| salt(secret, name, s)).collect();
+            // chain[s] commits to path[s..]
+            let mut chain = vec![TERMINAL.to_string(); path.len() + 1];
+            for s in (0..path.len()).rev() {
+                chain[s] = link(&salts[s], &path[s], &chain[s + 1]);
+            }
+            let mut next = 0;
+            let publications = announcements.schedule[name]
+                .iter()
+                .enumerate()
+                .map(|(t, &announced)| {
+                    let end = announced.min(path.len()).max(next);
+                    let revealed = (next..end)
+                        .map(|s| Reveal {
+                            step: path[s],
+                            salt: salts[s].clone(),
+                        })
+                        .collect();
+                    next = end;
+                    Publication {
+                        t,
+                        revealed,
+                        commitment: chain[end].clone(),
+                    }
+                })
+                .collect();
+            let transcript = AgentTranscript {
+                head: chain[0].clone(),
+                publications,
+            };
+            (name.clone(), transcript)
+        })
+        .collect();
+    Transcript { agents }
+}
+
+/// Every violation of `transcript`: publications missing or out of order, steps revealed out of
+/// order or later than `announcements` announce them, and reveals that do not hash to the
+/// commitment published before them. Every plan is due up to `makespan + 1`, as padded by
+/// [`extend_stay_in_place`](crate::extend_stay_in_place), so a plan ending earlier counts as
+/// withholding the rest.
+pub fn verify(
+    transcript: &Transcript,
+    announcements: &Announcements,
+    makespan: usize,
+) -> Vec<CommitmentViolation> {
+    let mut violations = Vec::new();
+    for (name, schedule) in &announcements.schedule {
+        let violation = |t: usize, kind: CommitmentViolationKind| CommitmentViolation {
+            agent: name.clone(),
+            t,
+            kind,
+        };
+        let agent = match transcript.agents.get(name) {
+            Some(agent) => agent,
+            None => {
+                violations.push(violation(0, CommitmentViolationKind::MissingTranscript));
+                continue;
+            }
+        };
+        let mut expected = &agent.head;
+        let mut next = 0;
+        for t in 0..schedule.len().max(agent.publications.len()) {
+            let publication = match agent.publications.get(t) {
+                Some(publication) => publication,
+                None => {
+                    violations.push(violation(t, CommitmentViolationKind::Missing));
+                    break;
+                }
+            };
+            if publication.t != t {
+                violations.push(violation(
+                    t,
+                    CommitmentViolationKind::OutOfOrder {
+                        found: publication.t,
+                    },
+                ));
+            }
+            for reveal in &publication.revealed {
+                if reveal.step.t != next {
+                    violations.push(violation(
+                        t,
+                        CommitmentViolationKind::Gap {
+                            expected: next,
+                            found: reveal.step.t,
+                        },
+                    ));
+                }
+                next = reveal.step.t + 1;
+            }
+            let announced = schedule.get(t).map_or(0, |&until| until.min(makespan + 2));
+            if next < announced {
+                violations.push(violation(
+                    t,
+                    CommitmentViolationKind::Withheld {
+                        announced,
+                        revealed: next,
+                    },
+                ));
+            }
+            let folded = publication
+                .revealed
+                .iter()
+                .rev()
+                .fold(publication.commitment.clone(), |tail, reveal| {
+                    link(&reveal.salt, &reveal.step, &tail)
+                });
+            if folded != *expected {
+                violations.push(violation(t, CommitmentViolationKind::Broken));
+            }
+            expected = &publication.commitment;
+        }
+    }
+    violations
+}
+
+fn salt(secret: &[u8], name: &str, s: usize) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(secret);
+    hasher.update(name.as_bytes());
+    hasher.update((s as u64).to_le_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn link(salt: &str, step: &TimedCoordinate, tail: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(step.x.to_le_bytes());
+    hasher.update(step.y.to_le_bytes());
+    hasher.update((step.t as u64).to_le_bytes());
+    hasher.update(tail.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::MapfInstance;
+    use crate::planner::plan_prioritized;
+    use crate::utils::{compute_kgrouped_announcements, extend_stay_in_place};
+
+    const INSTANCE: &str = "
+map:
+  dimensions: [10, 8]
+  obstacles: [[4, 3], [5, 3], [4, 4]]
+agents:
+  - {name: agent0, start: [1, 1], goal: [8, 6]}
+  - {name: agent1, start: [8, 1], goal: [1, 6]}
+";
+
+    #[test]
+    fn rewriting_a_committed_step_breaks_the_chain() {
+        let instance: MapfInstance = serde_yaml::from_str(INSTANCE).unwrap();
+        let mut solution = plan_prioritized(&instance).unwrap();
+        extend_stay_in_place(&mut solution);
+        let makespan = solution.statistics.makespan;
+        let announcements = compute_kgrouped_announcements(&instance.agents, 3, makespan);
+        let transcript = commit(&solution, &announcements, b"secret");
+        assert_eq!(verify(&transcript, &announcements, makespan), vec![]);
+        let agent0 = &transcript.agents["agent0"];
+        assert_eq!(agent0.publications.last().unwrap().commitment, TERMINAL);
+
+        // agent0 reveals a different step 4 than it committed to at t=0
+        let publication = agent0
+            .publications
+            .iter()
+            .position(|publication| publication.revealed.iter().any(|r| r.step.t == 4))
+            .unwrap();
+        let mut tampered = transcript.clone();
+        let reveal = tampered.agents.get_mut("agent0").unwrap().publications[publication]
+            .revealed
+            .iter_mut()
+            .find(|reveal| reveal.step.t == 4)
+            .unwrap();
+        reveal.step.x += 1;
+        assert_eq!(
+            verify(&tampered, &announcements, makespan),
+            vec![CommitmentViolation {
+                agent: "agent0".to_string(),
+                t: publication,
+                kind: CommitmentViolationKind::Broken,
+            }]
+        );
+
+        // or follows a new plan from then on, consistently with itself but not with its head
+        let mut rewritten = solution.clone();
+        for step in &mut rewritten.schedule.get_mut("agent0").unwrap()[4..] {
+            step.y += 1;
+        }
+        let mut tampered = commit(&rewritten, &announcements, b"secret");
+        tampered.agents.get_mut("agent0").unwrap().head = agent0.head.clone();
+        assert_eq!(
+            verify(&tampered, &announcements, makespan),
+            vec![CommitmentViolation {
+                agent: "agent0".to_string(),
+                t: 0,
+                kind: CommitmentViolationKind::Broken,
+            }]
+        );
+
+        // or holds back a step that is due
+        let mut tampered = transcript.clone();
+        let withheld = tampered.agents.get_mut("agent1").unwrap();
+        let revealed = withheld.publications[0].revealed.pop().unwrap();
+        withheld.publications[0].commitment = link(
+            &revealed.salt,
+            &revealed.step,
+            &withheld.publications[0].commitment,
+        );
+        withheld.publications[1].revealed.insert(0, revealed);
+        assert_eq!(
+            verify(&tampered, &announcements, makespan),
+            vec![CommitmentViolation {
+                agent: "agent1".to_string(),
+                t: 0,
+                kind: CommitmentViolationKind::Withheld {
+                    announced: 4,
+                    revealed: 3,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn a_transcript_cut_short_is_reported() {
+        let instance: MapfInstance = serde_yaml::from_str(INSTANCE).unwrap();
+        let mut solution = plan_prioritized(&instance).unwrap();
+        extend_stay_in_place(&mut solution);
+        let makespan = solution.statistics.makespan;
+        let announcements = compute_kgrouped_announcements(&instance.agents, 3, makespan);
+
+        // agent0 goes silent after t=2
+        let mut truncated = commit(&solution, &announcements, b"secret");
+        truncated
+            .agents
+            .get_mut("agent0")
+            .unwrap()
+            .publications
+            .truncate(3);
+        assert_eq!(
+            verify(&truncated, &announcements, makespan),
+            vec![CommitmentViolation {
+                agent: "agent0".to_string(),
+                t: 3,
+                kind: CommitmentViolationKind::Missing,
+            }]
+        );
+
+        // agent1 commits to a plan that ends early and claims there is nothing left to reveal
+        let mut short = solution.clone();
+        short.schedule.get_mut("agent1").unwrap().truncate(5);
+        let transcript = commit(&short, &announcements, b"secret");
+        let ended = &transcript.agents["agent1"].publications[3];
+        assert_eq!(ended.commitment, TERMINAL);
+        assert_eq!(
+            verify(&transcript, &announcements, makespan)[0],
+            CommitmentViolation {
+                agent: "agent1".to_string(),
+                t: 3,
+                kind: CommitmentViolationKind::Withheld {
+                    announced: 7,
+                    revealed: 5,
+                },
+            }
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Index-based form of an instance, its plan and announcements for the analyses' inner loops,
+//! see [`Compiled`].
+
+use decorum::N64;
+use itertools::Itertools;
+use std::cmp::max;
+use std::collections::HashMap;
+
+use crate::channel::Channel;
+use crate::energy::Energy;
+use crate::inputs::{Announcements, Coordinate, MapfInstance, MapfSolution, TimedCoordinate};
+
+/// Position of an agent in [`MapfInstance::agents`].
+pub type AgentId = u32;
+
+/// An instance, its padded plan and announcements with agents numbered by [`AgentId`], paths
+/// stored back to back and announcements as a dense agent by time matrix. Built once per
+/// analysis so the inner loops index arrays instead of hashing agent names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Compiled {
+    names: Vec<String>,
+    ids: HashMap<String, AgentId>,
+    pub dimensions: Coordinate,
+    /// In row-major order.
+    pub obstacles: Vec<Coordinate>,
+    pub makespan: usize,
+    // path_len time steps per agent
+    paths: Vec<TimedCoordinate>,
+    path_len: usize,
+    // announced_len time steps per agent
+    announced: Vec<usize>,
+    announced_len: usize,
+    // listener by speaker by time, see `Channel::knowledge`, if announcements aren't perfect
+    knowledge: Option<Vec<usize>>,
+    energy: Vec<Option<Energy>>,
+    pub min_inter_announcement_time: usize,
+    pub min_lookahead: usize,
+    pub avg_lookahead: N64,
+}
+
+impl Compiled {
+    /// `solution` must be padded with [`extend_stay_in_place`](crate::extend_stay_in_place) and
+    /// both it and `announcements` must cover every agent of `instance`, as checked by the
+    /// loaders.
+    pub fn new(
+        instance: &MapfInstance,
+        solution: &MapfSolution,
+        announcements: &Announcements,
+    ) -> Compiled {
+        let names: Vec<String> = instance
+            .agents
+            .iter()
+            .map(|agent| agent.name.clone())
+            .collect();
+        let path_len = solution.statistics.makespan + 2;
+        let announced_len = announcements
+            .schedule
+            .values()
+            .map(|sched| sched.len())
+            .min()
+            .unwrap_or(0);
+        let mut paths = Vec::with_capacity(names.len() * path_len);
+        let mut announced = Vec::with_capacity(names.len() * announced_len);
+        for name in &names {
+            paths.extend_from_slice(&solution.schedule[name][..path_len]);
+            announced.extend_from_slice(&announcements.schedule[name][..announced_len]);
+        }
+        Compiled {
+            ids: names
+                .iter()
+                .enumerate()
+                .map(|(id, name)| (name.clone(), id as AgentId))
+                .collect(),
+            names,
+            dimensions: instance.map.dimensions,
+            obstacles: instance
+                .map
+                .obstacles
+                .iter()
+                .copied()
+                .sorted_by_key(|obstacle| (obstacle.y, obstacle.x))
+                .collect(),
+            makespan: solution.statistics.makespan,
+            paths,
+            path_len,
+            announced,
+            announced_len,
+            knowledge: None,
+            energy: instance.agents.iter().map(|agent| agent.energy).collect(),
+            min_inter_announcement_time: announcements.min_inter_announcement_time(),
+            min_lookahead: announcements.min_lookahead(),
+            avg_lookahead: announcements.avg_lookahead(),
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<AgentId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, agent: AgentId) -> &String {
+        &self.names[agent as usize]
+    }
+
+    /// `cell` and the cells next to it on the map.
+    pub fn neighborhood(&self, cell: Coordinate) -> impl Iterator<Item = Coordinate> {
+        let dimensions = self.dimensions;
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .map(move |(dx, dy)| (x + dx, y + dy))
+            .filter(move |&(x, y)| {
+                x >= 0 && y >= 0 && x < dimensions.x as i32 && y < dimensions.y as i32
+            })
+            .map(|(x, y)| Coordinate {
+                x: x as u16,
+                y: y as u16,
+            })
+    }
+
+    /// Every agent, in instance order.
+    pub fn agents(&self) -> impl Iterator<Item = AgentId> {
+        0..self.names.len() as AgentId
+    }
+
+    /// Every agent but `attacker`.
+    pub fn defenders(&self, attacker: AgentId) -> impl Iterator<Item = AgentId> {
+        self.agents().filter(move |&agent| agent != attacker)
+    }
+
+    pub fn agent_count(&self) -> usize {
+        self.names.len()
+    }
+
+    /// `agent`'s padded plan, `0..=makespan + 1`.
+    pub fn path(&self, agent: AgentId) -> &[TimedCoordinate] {
+        let start = agent as usize * self.path_len;
+        &self.paths[start..start + self.path_len]
+    }
+
+    /// Where `agent`'s plan has it at `t`.
+    pub fn at(&self, agent: AgentId, t: usize) -> TimedCoordinate {
+        self.paths[agent as usize * self.path_len + t]
+    }
+
+    /// The first time step of `agent`'s plan not yet announced at `t`.
+    pub fn announced(&self, agent: AgentId, t: usize) -> usize {
+        self.announced[agent as usize * self.announced_len + t]
+    }
+
+    /// Number of time steps the announcements cover.
+    pub fn announced_len(&self) -> usize {
+        self.announced_len
+    }
+
+    /// Delivers the announcements over `channel` rather than to everyone at once.
+    pub fn with_channel(mut self, channel: &Channel) -> Compiled {
+        self.knowledge = Some(channel.knowledge(&self));
+        self
+    }
+
+    /// The first time step of `speaker`'s plan `listener` has not heard of at `t`.
+    pub fn known(&self, listener: AgentId, speaker: AgentId, t: usize) -> usize {
+        match &self.knowledge {
+            Some(knowledge) => {
+                let agents = self.agent_count();
+                knowledge[(listener as usize * agents + speaker as usize) * self.announced_len + t]
+            }
+            None => self.announced(speaker, t),
+        }
+    }
+
+    /// Whether `listener` has heard where `speaker` is to be at `t` by then, and so can tell
+    /// whether it is there.
+    pub fn knows_step(&self, listener: AgentId, speaker: AgentId, t: usize) -> bool {
+        self.knowledge.is_none() || t < self.known(listener, speaker, t)
+    }
+
+    /// How far each agent's plan counts as known at `t` when `attacker` deviates: what the
+    /// attacker heard of every defender's, and what every defender heard of the attacker's.
+    pub fn horizons(&self, attacker: AgentId, t: usize) -> Vec<usize> {
+        self.agents()
+            .map(|agent| {
+                if agent == attacker {
+                    self.defenders(attacker)
+                        .map(|defender| self.known(defender, attacker, t))
+                        .min()
+                        .unwrap_or_else(|| self.announced(attacker, t))
+                } else {
+                    self.known(attacker, agent, t)
+                }
+            })
+            .collect()
+    }
+
+    /// `agent`'s battery, if it has one.
+    pub fn energy(&self, agent: AgentId) -> Option<&Energy> {
+        self.energy[agent as usize].as_ref()
+    }
+
+    /// What `agent`'s plan draws from its battery by `t`, 0 without one.
+    pub fn drawn(&self, agent: AgentId, t: usize) -> f64 {
+        match self.energy(agent) {
+            Some(energy) => {
+                let path = self.path(agent);
+                (1..t + 1)
+                    .map(|s| energy.step(path[s - 1].into(), path[s].into()))
+                    .sum()
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Whether `agent`, having drawn `spent` by the time it is at `from`, can be at `to` and
+    /// still reach its goal by the makespan on its battery, as far as the least those moves can
+    /// draw tells. Always without a battery.
+    pub fn affords(
+        &self,
+        agent: AgentId,
+        spent: f64,
+        from: TimedCoordinate,
+        to: TimedCoordinate,
+    ) -> bool {
+        match self.energy(agent) {
+            Some(energy) => {
+                let goal = self.at(agent, self.makespan).into();
+                let needed = spent
+                    + energy.least(from.into(), to.into(), to.t.saturating_sub(from.t))
+                    + energy.least(to.into(), goal, self.makespan.saturating_sub(to.t));
+                energy.holds(needed)
+            }
+            None => true,
+        }
+    }
+
+    /// See [`MapfSolution::max_inter_observation_time`].
+    pub fn max_inter_observation_time(&self, attacker: AgentId) -> usize {
+        let mut iot = 1;
+        let mut miot = 1;
+        for t in 0..self.makespan + 1 {
+            let position = self.at(attacker, t);
+            if self
+                .defenders(attacker)
+                .any(|defender| self.at(defender, t).adj(&position))
+            {
+                iot = 1;
+            } else {
+                iot += 1;
+            }
+            miot = max(miot, iot);
+        }
+        miot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planner::plan_prioritized;
+    use crate::utils::{compute_kgrouped_announcements, extend_stay_in_place};
+
+    const INSTANCE: &str = "
+map:
+  dimensions: [10, 8]
+  obstacles: [[4, 3], [5, 3], [4, 4]]
+agents:
+  - {name: agent0, start: [1, 1], goal: [8, 6], energy: {budget: 100, per_move: 1.0, per_wait: 0.5}}
+  - {name: agent1, start: [8, 1], goal: [1, 6]}
+  - {name: agent2, start: [1, 6], goal: [8, 1]}
+";
+
+    fn inputs() -> (MapfInstance, MapfSolution, Announcements) {
+        let instance: MapfInstance = serde_yaml::from_str(INSTANCE).unwrap();
+        let mut solution = plan_prioritized(&instance).unwrap();
+        extend_stay_in_place(&mut solution);
+        let announcements =
+            compute_kgrouped_announcements(&instance.agents, 3, solution.statistics.makespan);
+        (instance, solution, announcements)
+    }
+
+    #[test]
+    fn indices_match_the_named_plan_and_announcements() {
+        let (instance, solution, announcements) = inputs();
+        let compiled = Compiled::new(&instance, &solution, &announcements);
+        assert_eq!(compiled.agent_count(), 3);
+        for (name, agent) in ["agent0", "agent1", "agent2"].iter().zip(compiled.agents()) {
+            assert_eq!(compiled.id(name), Some(agent));
+            assert_eq!(compiled.name(agent), name);
+            let path = &solution.schedule[*name];
+            assert_eq!(compiled.path(agent), &path[..compiled.makespan + 2]);
+            for (t, &step) in path.iter().enumerate().take(compiled.makespan + 2) {
+                assert_eq!(compiled.at(agent, t), step);
+            }
+            for t in 0..compiled.announced_len() {
+                assert_eq!(
+                    compiled.announced(agent, t),
+                    announcements.schedule[*name][t]
+                );
+                // without a channel everyone knows what is announced
+                for listener in compiled.agents() {
+                    assert_eq!(
+                        compiled.known(listener, agent, t),
+                        compiled.announced(agent, t)
+                    );
+                }
+            }
+            assert_eq!(
+                compiled.max_inter_observation_time(agent),
+                solution.max_inter_observation_time(&name.to_string())
+            );
+        }
+        let horizons = compiled.horizons(1, 4);
+        let announced: Vec<usize> = compiled
+            .agents()
+            .map(|agent| compiled.announced(agent, 4))
+            .collect();
+        assert_eq!(horizons, announced);
+    }
+
+    #[test]
+    fn a_late_channel_shifts_what_the_others_know() {
+        let (instance, solution, announcements) = inputs();
+        let channel = Channel {
+            drop_probability: 0.0,
+            latency: 1,
+            range: None,
+            seed: 0,
+        };
+        let compiled = Compiled::new(&instance, &solution, &announcements).with_channel(&channel);
+        for speaker in compiled.agents() {
+            for listener in compiled.agents() {
+                for t in 0..compiled.announced_len() {
+                    let known = compiled.known(listener, speaker, t);
+                    if listener == speaker {
+                        assert_eq!(known, compiled.announced(speaker, t));
+                    } else if t == 0 {
+                        assert_eq!(known, 0);
+                    } else {
+                        assert_eq!(known, compiled.announced(speaker, t - 1));
+                    }
+                }
+            }
+        }
+        // the groups announced at 3 have not arrived yet, in either direction
+        let horizons = compiled.horizons(2, 3);
+        let late: Vec<usize> = compiled
+            .agents()
+            .map(|agent| compiled.announced(agent, 2))
+            .collect();
+        assert_eq!(horizons, late);
+    }
+
+    #[test]
+    fn only_agents_with_a_battery_draw_from_it() {
+        let (mut instance, solution, announcements) = inputs();
+        let compiled = Compiled::new(&instance, &solution, &announcements);
+        let energy = instance.agents[0].energy.unwrap();
+        let path = &solution.schedule["agent0"];
+        let end = compiled.makespan;
+        let drawn: f64 = (1..end + 1)
+            .map(|t| energy.step(path[t - 1].into(), path[t].into()))
+            .sum();
+        assert_eq!(compiled.drawn(0, end), drawn);
+        assert_eq!(compiled.drawn(1, end), 0.0);
+        // following its own plan stays within the budget
+        for t in 0..end {
+            let spent = compiled.drawn(0, t);
+            assert!(compiled.affords(0, spent, compiled.at(0, t), compiled.at(0, t + 1)));
+        }
+
+        // too little to even get to the goal
+        instance.agents[0].energy = Some(Energy {
+            budget: 1.0,
+            ..energy
+        });
+        let compiled = Compiled::new(&instance, &solution, &announcements);
+        assert!(!compiled.affords(0, 0.0, compiled.at(0, 0), compiled.at(0, 1)));
+        assert!(compiled.affords(1, 0.0, compiled.at(1, 0), compiled.at(1, 1)));
+    }
+}
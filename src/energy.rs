@@ -0,0 +1,220 @@
+//! Battery budgets, so a deviation costs the attacker energy its plan did not account for, see
+//! [`Energy`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::inputs::{Coordinate, TimedCoordinate};
+
+// absorbs the rounding of sums of draws compared with budgets they exactly meet
+const ROUNDING: f64 = 1e-9;
+
+/// An agent's battery: what it holds at time step 0 and what each move and each wait draws.
+/// Agents keep drawing until the makespan, waiting at their goals once they arrive.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Energy {
+    pub budget: f64,
+    pub per_move: f64,
+    pub per_wait: f64,
+}
+
+impl Energy {
+    /// What one time step from `from` to `to` draws.
+    pub fn step(&self, from: Coordinate, to: Coordinate) -> f64 {
+        if from == to {
+            self.per_wait
+        } else {
+            self.per_move
+        }
+    }
+
+    /// The least going from `from` to `to` in `steps` time steps can draw, ignoring obstacles
+    /// and other agents. Moves the distance needs, and the cheaper of a wait or a move for
+    /// every other step.
+    pub fn least(&self, from: Coordinate, to: Coordinate, steps: usize) -> f64 {
+        let moves = from.manh_dist(&to);
+        moves as f64 * self.per_move
+            + steps.saturating_sub(moves) as f64 * self.per_move.min(self.per_wait)
+    }
+
+    /// Whether the battery holds `needed`.
+    pub fn holds(&self, needed: f64) -> bool {
+        needed <= self.budget + ROUNDING
+    }
+
+    /// The first time step by which following `path`, and waiting at its end until `makespan`,
+    /// has drawn more than the budget, with what the whole of it draws.
+    pub fn exhausted(&self, path: &[TimedCoordinate], makespan: usize) -> Option<(usize, f64)> {
+        let mut drawn = 0.0;
+        let mut exhausted = None;
+        for t in 1..path.len() {
+            drawn += self.step(path[t - 1].into(), path[t].into());
+            if exhausted.is_none() && !self.holds(drawn) {
+                exhausted = Some(t);
+            }
+        }
+        for t in path.len()..makespan + 1 {
+            drawn += self.per_wait;
+            if exhausted.is_none() && !self.holds(drawn) {
+                exhausted = Some(t);
+            }
+        }
+        exhausted.map(|t| (t, drawn))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiled::Compiled;
+    use crate::experiments::{trace_bold_attempt, trace_cautious_attempt, BoldEvent};
+    use crate::inputs::{Announcements, MapfInstance, MapfSolution, Statistics, ViolationKind};
+    use crate::utils::{compute_kahead_announcements, extend_stay_in_place};
+    use std::collections::BTreeMap;
+
+    // a 5 by 2 grid the agent crosses along the bottom row, drawing 4.8 with two waits
+    fn instance(budget: f64) -> MapfInstance {
+        serde_yaml::from_str(&format!(
+            "
+map: {{dimensions: [5, 2], obstacles: []}}
+agents:
+  - {{name: agent0, start: [0, 0], goal: [4, 0],
+      energy: {{budget: {}, per_move: 1.0, per_wait: 0.4}}}}
+",
+            budget
+        ))
+        .unwrap()
+    }
+
+    fn solution() -> MapfSolution {
+        crossing(&[0, 1, 2, 2, 2, 3, 4])
+    }
+
+    // agent0 along the bottom row through `xs`, one per time step
+    fn crossing(xs: &[u16]) -> MapfSolution {
+        let path = xs
+            .iter()
+            .enumerate()
+            .map(|(t, &x)| TimedCoordinate { x, y: 0, t })
+            .collect();
+        let makespan = xs.len() - 1;
+        MapfSolution {
+            statistics: Statistics {
+                cost: makespan as u32,
+                makespan,
+                runtime: 0.0,
+                highLevelExpanded: 0,
+                lowLevelExpanded: 0,
+            },
+            schedule: vec![("agent0".to_string(), path)].into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn a_battery_that_just_covers_the_plan_leaves_no_room_to_deviate() {
+        let exact = instance(4.8);
+        assert_eq!(solution().validate(&exact), vec![]);
+        let flat = instance(4.0);
+        let violations = solution().validate(&flat);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].t, 6);
+        assert!(matches!(
+            violations[0].kind,
+            ViolationKind::OverBudget { needed, budget } if (needed - 4.8).abs() < 1e-9 && budget == 4.0
+        ));
+
+        let compile = |instance: &MapfInstance| {
+            let mut solution = solution();
+            extend_stay_in_place(&mut solution);
+            let announcements = compute_kahead_announcements(&instance.agents, 2, 6);
+            Compiled::new(instance, &solution, &announcements)
+        };
+        let compiled = compile(&exact);
+        let detour = TimedCoordinate { x: 2, y: 1, t: 3 };
+        for t in 1..7 {
+            let at = compiled.at(0, t);
+            assert!(compiled.affords(0, compiled.drawn(0, t), at, at));
+        }
+        // stepping up and back down instead of waiting draws 1.2 more
+        assert!(!compiled.affords(0, compiled.drawn(0, 2), compiled.at(0, 2), detour));
+        let spare = compile(&instance(6.0));
+        assert!(spare.affords(0, spare.drawn(0, 2), spare.at(0, 2), detour));
+    }
+
+    // the same crossing on a 5 by 3 grid, waiting 4 steps at (2, 0), drawing 5.6: going up to
+    // (2, 1) and back instead of two of the waits draws 6.8, up to (2, 2) and back 8
+    fn waiting(budget: f64) -> (MapfInstance, MapfSolution, Announcements) {
+        let instance: MapfInstance = serde_yaml::from_str(&format!(
+            "
+map: {{dimensions: [5, 3], obstacles: []}}
+agents:
+  - {{name: agent0, start: [0, 0], goal: [4, 0],
+      energy: {{budget: {}, per_move: 1.0, per_wait: 0.4}}}}
+",
+            budget
+        ))
+        .unwrap();
+        let mut solution = crossing(&[0, 1, 2, 2, 2, 2, 2, 3, 4]);
+        extend_stay_in_place(&mut solution);
+        let announcements = compute_kahead_announcements(&instance.agents, 2, 8);
+        (instance, solution, announcements)
+    }
+
+    #[test]
+    fn a_bold_attacker_only_goes_for_targets_its_battery_can_pay_for() {
+        let attempt = |budget, x, y| {
+            let (instance, solution, announcements) = waiting(budget);
+            let safe = Coordinate { x, y };
+            trace_bold_attempt(&instance, &solution, &announcements, "agent0", &safe, true)
+        };
+
+        // exactly enough for the plan, it never leaves it
+        let (stays, trace) = attempt(5.6, 2, 1);
+        assert!(!stays.attempted());
+        assert!(!stays.dangerous);
+        assert!(!stays.detected);
+        let (_, solution, _) = waiting(5.6);
+        let positions: Vec<TimedCoordinate> =
+            trace.steps.iter().map(|step| step.position).collect();
+        assert_eq!(positions, solution.schedule["agent0"][..positions.len()]);
+
+        // enough for the near target, but the far one is out of reach until the budget allows it
+        let (near, trace) = attempt(7.0, 2, 1);
+        assert!(near.dangerous);
+        assert!(!trace
+            .steps
+            .iter()
+            .any(|step| step.events.contains(&BoldEvent::Drained)));
+        let (far, _) = attempt(7.0, 2, 2);
+        assert!(!far.attempted());
+        assert!(!far.dangerous);
+        let (far, _) = attempt(8.0, 2, 2);
+        assert!(far.dangerous);
+    }
+
+    #[test]
+    fn a_tight_battery_shrinks_the_cautious_flood() {
+        let floods = |budget| {
+            let (instance, solution, announcements) = waiting(budget);
+            let safe = Coordinate { x: 2, y: 2 };
+            let (_, trace) =
+                trace_cautious_attempt(&instance, &solution, &announcements, "agent0", &safe);
+            trace
+                .steps
+                .into_iter()
+                .map(|step| ((step.t, step.s), step.floods["agent0"].clone()))
+                .collect::<Vec<_>>()
+        };
+        let tight = floods(5.6);
+        let loose: BTreeMap<_, _> = floods(100.0).into_iter().collect();
+        for (at, tight) in &tight {
+            // it cannot afford to leave the bottom row
+            assert!(tight.iter().all(|cell| cell.y == 0));
+            if let Some(loose) = loose.get(at) {
+                assert!(tight.is_subset(loose));
+            }
+        }
+        assert!(loose
+            .values()
+            .any(|flood| flood.iter().any(|cell| cell.y > 0)));
+    }
+}
@@ -0,0 +1,164 @@
+//! Announcements addressed to each defender separately, which lets an attacker tell different
+//! defenders different paths, and the cross-checks that catch it, see [`PerRecipient`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::compiled::{AgentId, Compiled};
+use crate::inputs::{Coordinate, TimedCoordinate};
+
+/// How an equivocating attacker lied and whether the defenders' cross-checks caught it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EquivocationOutcome {
+    /// Defenders told the deviation rather than the plan.
+    pub recipients: usize,
+    /// Time step the attacker reached the target, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reached_at: Option<usize>,
+    /// Time step two defenders that heard different paths first compared them, if they did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caught_at: Option<usize>,
+}
+
+impl EquivocationOutcome {
+    /// Whether the cross-checks caught the attacker no later than it reached the target, `None`
+    /// if it never did.
+    pub fn caught_before_target(&self) -> Option<bool> {
+        self.reached_at
+            .map(|reached| matches!(self.caught_at, Some(caught) if caught <= reached))
+    }
+}
+
+/// The attacker's path as announced to each defender. An equivocating attacker tells its
+/// deviation to every defender that would otherwise notice it, by seeing it off its plan or not
+/// seeing it where the plan has it, and its plan to everyone else. With hindsight of who needs
+/// to be lied to, which only makes it harder to catch.
+pub struct PerRecipient<'a> {
+    plan: &'a [TimedCoordinate],
+    deviation: &'a [TimedCoordinate],
+    told_deviation: Vec<bool>,
+}
+
+impl<'a> PerRecipient<'a> {
+    /// `deviation` is where the attacker went, from time step 0.
+    pub fn new(
+        compiled: &'a Compiled,
+        attacker: AgentId,
+        deviation: &'a [TimedCoordinate],
+    ) -> PerRecipient<'a> {
+        let plan = compiled.path(attacker);
+        let told_deviation = compiled
+            .agents()
+            .map(|agent| {
+                agent != attacker
+                    && deviation.iter().enumerate().any(|(t, &actual)| {
+                        let position = compiled.at(agent, t);
+                        Coordinate::from(actual) != plan[t].into()
+                            && (position.adj(&actual) || position.adj(&plan[t]))
+                    })
+            })
+            .collect();
+        PerRecipient {
+            plan,
+            deviation,
+            told_deviation,
+        }
+    }
+
+    /// Where `recipient` was told the attacker would be at `t`.
+    pub fn heard(&self, recipient: AgentId, t: usize) -> Coordinate {
+        if self.told_deviation[recipient as usize] {
+            self.deviation[t].into()
+        } else {
+            self.plan[t].into()
+        }
+    }
+
+    /// Number of defenders told the deviation.
+    pub fn recipients(&self) -> usize {
+        self.told_deviation.iter().filter(|&&told| told).count()
+    }
+
+    /// The first time step two defenders co-observing each other find that they were told
+    /// different waypoints among those both had heard of by then.
+    pub fn cross_check(&self, compiled: &Compiled, attacker: AgentId) -> Option<usize> {
+        (0..self.deviation.len()).find(|&t| {
+            compiled.defenders(attacker).any(|a| {
+                compiled.defenders(attacker).any(|b| {
+                    // the steps both have heard of by then
+                    let announced = compiled
+                        .known(a, attacker, t)
+                        .min(compiled.known(b, attacker, t))
+                        .min(self.deviation.len());
+                    self.told_deviation[a as usize]
+                        && !self.told_deviation[b as usize]
+                        && compiled.at(a, t).adj(&compiled.at(b, t))
+                        && (0..announced).any(|s| self.heard(a, s) != self.heard(b, s))
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::{MapfInstance, MapfSolution, Statistics};
+    use crate::utils::compute_kahead_announcements;
+
+    fn compiled(bystander: (usize, usize)) -> Compiled {
+        let instance: MapfInstance = serde_yaml::from_str(&format!(
+            "
+map: {{dimensions: [5, 3], obstacles: []}}
+agents:
+  - {{name: attacker, start: [2, 0], goal: [2, 0]}}
+  - {{name: near, start: [3, 1], goal: [3, 1]}}
+  - {{name: bystander, start: [{}, {}], goal: [{0}, {1}]}}
+",
+            bystander.0, bystander.1
+        ))
+        .unwrap();
+        let makespan = 3;
+        let solution = MapfSolution {
+            statistics: Statistics {
+                cost: 0,
+                makespan,
+                runtime: 0.0,
+                highLevelExpanded: 0,
+                lowLevelExpanded: 0,
+            },
+            schedule: instance
+                .agents
+                .iter()
+                .map(|agent| {
+                    let path = (0..makespan + 2).map(|t| agent.start.as_time(t)).collect();
+                    (agent.name.clone(), path)
+                })
+                .collect(),
+        };
+        let announcements = compute_kahead_announcements(&instance.agents, 2, makespan);
+        Compiled::new(&instance, &solution, &announcements)
+    }
+
+    #[test]
+    fn defenders_that_heard_different_paths_catch_the_attacker_when_they_meet() {
+        // the attacker steps next to `near` at 1 and back, `near` must be told so
+        let deviation: Vec<_> = [(2, 0), (2, 1), (2, 0), (2, 0)]
+            .iter()
+            .enumerate()
+            .map(|(t, &(x, y))| TimedCoordinate { x, y, t })
+            .collect();
+
+        let meeting = compiled((4, 1));
+        let told = PerRecipient::new(&meeting, 0, &deviation);
+        assert_eq!(told.recipients(), 1);
+        assert_eq!(told.heard(1, 1), Coordinate { x: 2, y: 1 });
+        assert_eq!(told.heard(2, 1), Coordinate { x: 2, y: 0 });
+        // the waypoint for 1 is announced from the start with a lookahead of 2
+        assert_eq!(told.cross_check(&meeting, 0), Some(0));
+
+        let apart = compiled((0, 2));
+        let told = PerRecipient::new(&apart, 0, &deviation);
+        assert_eq!(told.recipients(), 1);
+        assert_eq!(told.cross_check(&apart, 0), None);
+    }
+}
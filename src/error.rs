@@ -0,0 +1,119 @@
+//! The project-wide error type and the exit codes the CLI reports for it.
+
+use std::fmt;
+use std::io;
+
+use crate::inputs::PlanViolation;
+
+/// Everything that can go wrong loading inputs, writing outputs or rendering plots.
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be opened, read or written.
+    Io { path: String, source: io::Error },
+    /// A file isn't valid YAML for the expected type.
+    Yaml {
+        path: String,
+        source: serde_yaml::Error,
+    },
+    /// A line of a JSON lines file isn't valid JSON for the expected type.
+    Json {
+        path: String,
+        line: usize,
+        source: serde_json::Error,
+    },
+    /// A solution violates its instance, see [`MapfSolution::validate`](crate::MapfSolution::validate).
+    Validation {
+        instance: String,
+        solution: String,
+        violations: Vec<PlanViolation>,
+    },
+    /// A command line argument can't be parsed as what it gives.
+    Argument {
+        name: String,
+        value: String,
+        reason: String,
+    },
+    /// Instance and solution (or announcements) don't describe the same agents or horizon.
+    Inconsistent {
+        instance: String,
+        other: String,
+        reason: String,
+    },
+    /// The planner found no solution.
+    NoSolution { instance: String },
+    /// Drawing a plot failed.
+    Plot(String),
+    /// Writing a summary table failed, or its format isn't supported by this build.
+    Export { path: String, reason: String },
+}
+
+/// Shorthand for results carrying an [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Process exit code for this error, distinct per kind so batch runs can tell them apart.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Io { .. } => 2,
+            Error::Yaml { .. } | Error::Json { .. } => 3,
+            Error::Validation { .. } | Error::Argument { .. } => 4,
+            Error::Inconsistent { .. } => 5,
+            Error::NoSolution { .. } => 6,
+            Error::Plot(_) => 7,
+            Error::Export { .. } => 8,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::Yaml { path, source } => write!(f, "error parsing {}: {}", path, source),
+            Error::Json { path, line, source } => {
+                write!(f, "error parsing {} line {}: {}", path, line, source)
+            }
+            Error::Validation {
+                instance,
+                solution,
+                violations,
+            } => {
+                write!(
+                    f,
+                    "{} is not a valid solution to {} ({} violations)",
+                    solution,
+                    instance,
+                    violations.len()
+                )?;
+                for violation in violations {
+                    write!(f, "\n  {}", violation)?;
+                }
+                Ok(())
+            }
+            Error::Argument {
+                name,
+                value,
+                reason,
+            } => write!(f, "invalid --{} {}: {}", name, value, reason),
+            Error::Inconsistent {
+                instance,
+                other,
+                reason,
+            } => write!(f, "{} doesn't match {}: {}", other, instance, reason),
+            Error::NoSolution { instance } => write!(f, "no solution found for {}", instance),
+            Error::Plot(why) => write!(f, "plotting error: {}", why),
+            Error::Export { path, reason } => write!(f, "{}: {}", path, reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Yaml { source, .. } => Some(source),
+            Error::Json { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -1,17 +1,36 @@
+//! Bold and cautious attacker analyses and their result types.
+
 use decorum::N64;
 use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
 use itertools::Itertools;
-use matrix_display::{cell, matrix, style, Format, MatrixDisplay};
 use petgraph::algo::astar;
 use petgraph::graphmap::DiGraphMap;
+use petgraph::Direction::Incoming;
 use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet};
-use std::io::Read;
+use std::iter;
+use std::time::{Duration, Instant};
 
+use crate::channel::Channel;
+use crate::checkpoint::{Attempt, Checkpoint};
+use crate::compiled::{AgentId, Compiled};
+use crate::equivocation::{EquivocationOutcome, PerRecipient};
+use crate::error;
 use crate::inputs::{Announcements, Coordinate, MapfInstance, MapfSolution, TimedCoordinate};
+use crate::provenance::Provenance;
+use crate::response::{Response, ResponseOutcome};
+use crate::sensing::{DetectionChances, Estimate, SensedDetections, SensingModel, Witnesses};
+
+/// How many agents the analyses try as the attacker, the first ones of the instance.
+pub const SAMPLED_ATTACKERS: usize = 10;
+/// How many obstacles the analyses try as the target, spread evenly over the map's obstacles in
+/// row-major order.
+pub const SAMPLED_TARGETS: usize = 10;
 
+/// Outcome of one bold attacker trying to reach one target.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct BoldAttemptResult {
     pub attacker_name: String,
@@ -23,6 +42,26 @@ pub struct BoldAttemptResult {
     pub min_inter_announcement_time: usize,
     pub min_lookahead: usize,
     pub avg_lookahead: Option<N64>,
+    /// Alarms over the Monte Carlo runs of the [`SensingModel`], if the analysis had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensed: Option<SensedDetections>,
+    /// How the attacker lied and whether cross-checks caught it, if it equivocated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equivocation: Option<EquivocationOutcome>,
+    /// Whether the defenders' responders stopped the attacker, if the analysis had a
+    /// [`Response`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<ResponseOutcome>,
+}
+
+impl Attempt for BoldAttemptResult {
+    fn attacker_name(&self) -> &str {
+        &self.attacker_name
+    }
+
+    fn safe(&self) -> &Coordinate {
+        &self.safe
+    }
 }
 
 impl BoldAttemptResult {
@@ -31,11 +70,16 @@ impl BoldAttemptResult {
     }
 }
 
+/// All bold attempts of one run, as written by `analyze-attackers -t bold`.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct BoldExperimentResult {
+    /// Absent in results written before provenance was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
     pub attempts: Vec<BoldAttemptResult>,
 }
 
+/// Whether one attacker can reach one target without ever being told apart from its plan.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct CautiousAttemptResult {
     pub attacker_name: String,
@@ -44,20 +88,222 @@ pub struct CautiousAttemptResult {
     pub min_inter_announcement_time: usize,
     pub min_lookahead: usize,
     pub avg_lookahead: Option<N64>,
-    pub secured: bool,
+    /// Written as `secured: true|false` before attempts had a budget, read either way.
+    #[serde(alias = "secured", deserialize_with = "verdict_or_flag")]
+    pub verdict: CautiousVerdict,
+}
+
+/// Outcome of a cautious attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CautiousVerdict {
+    /// Every deviation reaching the target is told apart from the plan.
+    Secured,
+    /// Some deviation reaches the target unnoticed.
+    NotSecured,
+    /// The attempt ran out of its [`CautiousBudget`] before deciding.
+    Unknown,
+}
+
+impl From<bool> for CautiousVerdict {
+    fn from(secured: bool) -> CautiousVerdict {
+        if secured {
+            CautiousVerdict::Secured
+        } else {
+            CautiousVerdict::NotSecured
+        }
+    }
+}
+
+fn verdict_or_flag<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<CautiousVerdict, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum VerdictOrFlag {
+        Verdict(CautiousVerdict),
+        Flag(bool),
+    }
+    Ok(match VerdictOrFlag::deserialize(deserializer)? {
+        VerdictOrFlag::Verdict(verdict) => verdict,
+        VerdictOrFlag::Flag(secured) => secured.into(),
+    })
+}
+
+/// Limits on a single cautious attempt, beyond which it gives up with
+/// [`CautiousVerdict::Unknown`] instead of holding up the rest of the analysis. Unlimited by
+/// default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CautiousBudget {
+    pub wall_time: Option<Duration>,
+    /// Iterations of the fixpoint loop, over all time steps and restarts. Every iteration keeps
+    /// the floods of one more time step, so this also bounds memory.
+    pub iterations: Option<usize>,
+}
+
+impl CautiousBudget {
+    fn exceeded(&self, started: Instant, iterations: usize) -> bool {
+        self.iterations.is_some_and(|limit| iterations > limit)
+            || self
+                .wall_time
+                .is_some_and(|limit| started.elapsed() > limit)
+    }
+}
+
+impl Attempt for CautiousAttemptResult {
+    fn attacker_name(&self) -> &str {
+        &self.attacker_name
+    }
+
+    fn safe(&self) -> &Coordinate {
+        &self.safe
+    }
 }
 
+/// All cautious attempts of one run, as written by `analyze-attackers -t cautious`.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct CautiousExperimentResult {
+    /// Absent in results written before provenance was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
     pub attempts: Vec<CautiousAttemptResult>,
 }
 
+/// Something the defenders noticed, or the attacker achieved, during a bold attempt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BoldEvent {
+    /// The deviation collided with another robot, ending the attempt.
+    Collision,
+    /// The deviation left the attacker's battery short of finishing its plan, which its
+    /// battery level gives away, ending the attempt.
+    Drained,
+    /// The attacker was seen where the announcements rule it out.
+    UnexpectedObservation,
+    /// `observer` expected to co-observe the attacker but didn't.
+    MissedObservation {
+        observer: String,
+    },
+    ReachedTarget,
+    /// The attacker knows an undetectable path to the target and back, ending the attempt.
+    KnownDeviation,
+}
+
+/// The attacker's state at one time step of a bold attempt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoldStep {
+    pub t: usize,
+    pub position: TimedCoordinate,
+    pub events: Vec<BoldEvent>,
+}
+
+/// Step-by-step record of one bold attempt, see [`trace_bold_attempt`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BoldTrace {
+    pub steps: Vec<BoldStep>,
+}
+
+impl BoldTrace {
+    fn record(trace: &mut Option<&mut BoldTrace>, event: BoldEvent) {
+        if let Some(step) = trace.as_mut().and_then(|trace| trace.steps.last_mut()) {
+            step.events.push(event);
+        }
+    }
+}
+
+/// What the cautious fixpoint loop did at one step, see [`CautiousStep`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CautiousEvent {
+    /// `agent`'s flood hit a dead end: the cell joined the conflicts and the floods restart
+    /// from `s = 0`.
+    Restart { agent: String },
+    /// The floods stopped changing before the attacker could be observed, so it isn't secured.
+    Fixpoint,
+    /// The attacker's flood meets the observed cells at `at`; `attack` is whether an attack
+    /// consistent with that observation exists.
+    Observable { at: Coordinate, attack: bool },
+}
+
+/// The floods at time `t + s` while checking time `t` of a cautious attempt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CautiousStep {
+    pub t: usize,
+    pub s: usize,
+    /// Cells each agent may occupy, the attacker's excluding any a defender may be in.
+    pub floods: HashMap<String, HashSet<Coordinate>>,
+    /// Cells some defender may observe.
+    pub observed: HashSet<Coordinate>,
+    /// Dead ends found so far, across all `t`.
+    pub conflicts: HashSet<TimedCoordinate>,
+    pub events: Vec<CautiousEvent>,
+}
+
+/// Every iteration of the fixpoint loop of one cautious attempt, see [`trace_cautious_attempt`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CautiousTrace {
+    pub steps: Vec<CautiousStep>,
+}
+
+impl CautiousTrace {
+    fn snapshot(
+        trace: &mut Option<&mut CautiousTrace>,
+        compiled: &Compiled,
+        t: usize,
+        s: usize,
+        floods: &[HashSet<Coordinate>],
+        attacker: AgentId,
+        conflicts: &HashSet<TimedCoordinate>,
+    ) {
+        if let Some(trace) = trace.as_mut() {
+            trace.steps.push(CautiousStep {
+                t,
+                s,
+                floods: compiled
+                    .agents()
+                    .map(|agent| (compiled.name(agent).clone(), floods[agent as usize].clone()))
+                    .collect(),
+                observed: defender_observed(floods, attacker),
+                conflicts: conflicts.clone(),
+                events: Vec::new(),
+            });
+        }
+    }
+
+    fn record(trace: &mut Option<&mut CautiousTrace>, event: CautiousEvent) {
+        if let Some(step) = trace.as_mut().and_then(|trace| trace.steps.last_mut()) {
+            step.events.push(event);
+        }
+    }
+}
+
 impl CautiousExperimentResult {
+    fn verdict_count(&self, verdict: CautiousVerdict) -> usize {
+        self.attempts
+            .iter()
+            .filter(|a| a.verdict == verdict)
+            .count()
+    }
     pub fn secure_count(&self) -> usize {
-        self.attempts.iter().filter(|a| a.secured).count()
+        self.verdict_count(CautiousVerdict::Secured)
     }
-    pub fn secure_rate(&self) -> f64 {
-        (self.secure_count() as f64) / (self.attempts.len() as f64)
+    pub fn unknown_count(&self) -> usize {
+        self.verdict_count(CautiousVerdict::Unknown)
+    }
+    /// Fraction of the decided attempts that are secured, attempts out of budget left out,
+    /// `None` if none was decided.
+    pub fn secure_rate(&self) -> Option<f64> {
+        let decided = self.attempts.len() - self.unknown_count();
+        if decided > 0 {
+            Some(self.secure_count() as f64 / decided as f64)
+        } else {
+            None
+        }
+    }
+    pub fn unknown_rate(&self) -> Option<f64> {
+        if !self.attempts.is_empty() {
+            Some(self.unknown_count() as f64 / self.attempts.len() as f64)
+        } else {
+            None
+        }
     }
 }
 
@@ -114,160 +360,332 @@ impl BoldExperimentResult {
             None
         }
     }
+    /// [`miss_rate`](Self::miss_rate) over the Monte Carlo runs of the dangerous attempts, `None`
+    /// if there are none or the analysis had no [`SensingModel`].
+    pub fn sensed_miss_rate(&self, confidence: f64) -> Option<Estimate> {
+        let (missed, runs) = self.sensed_runs(true, |sensed| sensed.runs - sensed.detected);
+        Estimate::of(missed, runs, confidence)
+    }
+    /// [`false_alarm_rate`](Self::false_alarm_rate) over the Monte Carlo runs of the
+    /// non-dangerous attempts.
+    pub fn sensed_false_alarm_rate(&self, confidence: f64) -> Option<Estimate> {
+        let (alarms, runs) = self.sensed_runs(false, |sensed| sensed.detected);
+        Estimate::of(alarms, runs, confidence)
+    }
+    /// Share of the dangerous equivocating attempts the cross-checks caught no later than the
+    /// attacker reached the target, `None` if there are none.
+    pub fn caught_before_target_rate(&self) -> Option<f64> {
+        let caught: Vec<bool> = self
+            .attempts
+            .iter()
+            .filter_map(|attempt| attempt.equivocation?.caught_before_target())
+            .collect();
+        if caught.is_empty() {
+            None
+        } else {
+            Some(caught.iter().filter(|&&caught| caught).count() as f64 / caught.len() as f64)
+        }
+    }
+    /// Share of the dangerous attempts the [`Response`] stopped before the attacker reached the
+    /// target, `None` if there are none or the analysis had no response.
+    pub fn stop_rate(&self) -> Option<f64> {
+        let stopped: Vec<bool> = self
+            .attempts
+            .iter()
+            .filter(|attempt| attempt.dangerous)
+            .filter_map(|attempt| Some(attempt.response.as_ref()?.stopped()))
+            .collect();
+        if stopped.is_empty() {
+            None
+        } else {
+            Some(stopped.iter().filter(|&&stopped| stopped).count() as f64 / stopped.len() as f64)
+        }
+    }
+    // `counted` and all runs summed over the attempts that are `dangerous` or not
+    fn sensed_runs<F>(&self, dangerous: bool, counted: F) -> (usize, usize)
+    where
+        F: Fn(&SensedDetections) -> usize,
+    {
+        self.attempts
+            .iter()
+            .filter(|attempt| attempt.dangerous == dangerous)
+            .filter_map(|attempt| attempt.sensed.as_ref())
+            .fold((0, 0), |(count, runs), sensed| {
+                (count + counted(sensed), runs + sensed.runs)
+            })
+    }
 }
 
+/// Runs [`run_cautious_attempt`] for a sample of attackers and target obstacles in parallel.
 pub fn run_cautious_analysis(
     instance: MapfInstance,
     solution: MapfSolution,
     announcements: Announcements,
+    channel: Option<&Channel>,
+    budget: &CautiousBudget,
 ) -> CautiousExperimentResult {
-    let pb = ProgressBar::new(100u64);
+    let compiled = compile(&instance, &solution, &announcements, channel);
+    CautiousExperimentResult {
+        provenance: None,
+        attempts: sampled_attempts(&compiled, None, |attacker, safe| {
+            cautious_attempt(&compiled, attacker, safe, budget, None)
+        })
+        .expect("nothing to record without a checkpoint"),
+    }
+}
+
+/// Like [`run_cautious_analysis`], but skips the attempts already in `checkpoint` and records
+/// every new one there as soon as it finishes.
+pub fn resume_cautious_analysis(
+    instance: MapfInstance,
+    solution: MapfSolution,
+    announcements: Announcements,
+    channel: Option<&Channel>,
+    budget: &CautiousBudget,
+    checkpoint: Checkpoint<CautiousAttemptResult>,
+) -> error::Result<CautiousExperimentResult> {
+    let compiled = compile(&instance, &solution, &announcements, channel);
+    Ok(CautiousExperimentResult {
+        provenance: None,
+        attempts: sampled_attempts(&compiled, Some(checkpoint), |attacker, safe| {
+            cautious_attempt(&compiled, attacker, safe, budget, None)
+        })?,
+    })
+}
+
+// the analyses' form of the inputs, with the announcements delivered over `channel` if given
+fn compile(
+    instance: &MapfInstance,
+    solution: &MapfSolution,
+    announcements: &Announcements,
+    channel: Option<&Channel>,
+) -> Compiled {
+    let compiled = Compiled::new(instance, solution, announcements);
+    match channel {
+        Some(channel) => compiled.with_channel(channel),
+        None => compiled,
+    }
+}
+
+// the attacker and target pairs the analyses try: the first agents and every so many obstacles
+// in row-major order, so every run of an instance samples the same pairs and checkpoints can be
+// resumed, while the targets still cover the whole map
+fn sampled_pairs(compiled: &Compiled) -> Vec<(AgentId, &Coordinate)> {
+    let obstacles = &compiled.obstacles;
+    let targets = min(obstacles.len(), SAMPLED_TARGETS);
+    compiled
+        .agents()
+        .take(SAMPLED_ATTACKERS)
+        .cartesian_product((0..targets).map(|i| &obstacles[i * obstacles.len() / targets]))
+        .collect()
+}
+
+// runs `attempt` in parallel on the sampled pairs missing from `checkpoint` and records each
+// result there, returning all attempts in sampled order whatever order they finished in
+fn sampled_attempts<T, F>(
+    compiled: &Compiled,
+    checkpoint: Option<Checkpoint<T>>,
+    attempt: F,
+) -> error::Result<Vec<T>>
+where
+    T: Attempt + Serialize + DeserializeOwned + Send + Sync,
+    F: Fn(AgentId, &Coordinate) -> T + Sync,
+{
+    let sampled = sampled_pairs(compiled);
+    let order: HashMap<(&str, &Coordinate), usize> = sampled
+        .iter()
+        .enumerate()
+        .map(|(i, &(attacker, safe))| ((compiled.name(attacker).as_str(), safe), i))
+        .collect();
+    let pairs: Vec<_> = sampled
+        .iter()
+        .copied()
+        .filter(|&(attacker, safe)| {
+            checkpoint
+                .as_ref()
+                .is_none_or(|checkpoint| !checkpoint.contains(compiled.name(attacker), safe))
+        })
+        .collect();
+    let pb = ProgressBar::new(pairs.len() as u64);
     pb.set_style(
         ProgressStyle::default_bar()
             .template("[{eta:>5}->{elapsed:>5}] [{wide_bar}] {pos:>7}/{len:>7}")
             .progress_chars("=> "),
     );
-    CautiousExperimentResult {
-        attempts: instance
-            .agents
-            .iter()
-            .take(10)
-            .cartesian_product(instance.map.obstacles.iter().take(10))
-            .par_bridge()
-            .progress_with(pb)
-            .map(|(attacker, safe)| {
-                run_cautious_attempt(&instance, &solution, &announcements, &attacker.name, safe)
-            })
-            .collect(),
-    }
+    let new = pairs
+        .into_par_iter()
+        .progress_with(pb)
+        .map(|(attacker, safe)| {
+            let res = attempt(attacker, safe);
+            if let Some(checkpoint) = &checkpoint {
+                checkpoint.record(&res)?;
+            }
+            Ok(res)
+        })
+        .collect::<error::Result<Vec<_>>>()?;
+    let mut attempts = checkpoint.map_or_else(Vec::new, Checkpoint::into_done);
+    attempts.extend(new);
+    attempts.sort_by_key(|attempt| {
+        order
+            .get(&(attempt.attacker_name(), attempt.safe()))
+            .copied()
+            .unwrap_or(usize::MAX)
+    });
+    Ok(attempts)
 }
 
-fn run_cautious_attempt(
+/// Decides whether `attacker_name` can reach `safe` while staying indistinguishable from its
+/// nominal plan to the other agents. `solution` must be padded with
+/// [`extend_stay_in_place`](crate::utils::extend_stay_in_place). Gives up with
+/// [`CautiousVerdict::Unknown`] once `budget` is exceeded.
+pub fn run_cautious_attempt(
     instance: &MapfInstance,
     solution: &MapfSolution,
     announcements: &Announcements,
-    attacker_name: &String,
+    attacker_name: &str,
     safe: &Coordinate,
+    budget: &CautiousBudget,
 ) -> CautiousAttemptResult {
-    let g = build_graph(&instance, &solution, safe);
+    let compiled = Compiled::new(instance, solution, announcements);
+    let attacker = attacker_id(&compiled, attacker_name);
+    cautious_attempt(&compiled, attacker, safe, budget, None)
+}
+
+fn attacker_id(compiled: &Compiled, attacker_name: &str) -> AgentId {
+    compiled
+        .id(attacker_name)
+        .unwrap_or_else(|| panic!("{} is not an agent of the instance", attacker_name))
+}
+
+/// Runs [`run_cautious_attempt`] without a budget, recording the floods, observed cells and
+/// conflicts at every iteration of its fixpoint loop.
+pub fn trace_cautious_attempt(
+    instance: &MapfInstance,
+    solution: &MapfSolution,
+    announcements: &Announcements,
+    attacker_name: &str,
+    safe: &Coordinate,
+) -> (CautiousAttemptResult, CautiousTrace) {
+    let compiled = Compiled::new(instance, solution, announcements);
+    let mut trace = CautiousTrace::default();
+    let res = cautious_attempt(
+        &compiled,
+        attacker_id(&compiled, attacker_name),
+        safe,
+        &CautiousBudget::default(),
+        Some(&mut trace),
+    );
+    (res, trace)
+}
+
+fn cautious_attempt(
+    compiled: &Compiled,
+    attacker: AgentId,
+    safe: &Coordinate,
+    budget: &CautiousBudget,
+    mut trace: Option<&mut CautiousTrace>,
+) -> CautiousAttemptResult {
+    let started = Instant::now();
+    let mut iterations = 0;
+    let g = build_graph(compiled, safe);
     let mut res = CautiousAttemptResult {
-        attacker_name: attacker_name.clone(),
+        attacker_name: compiled.name(attacker).clone(),
         safe: *safe,
-        max_inter_observation_time: solution.max_inter_observation_time(&attacker_name),
-        min_inter_announcement_time: announcements.min_inter_announcement_time(),
-        min_lookahead: announcements.min_lookahead(),
-        avg_lookahead: Some(announcements.avg_lookahead()),
-        secured: true,
+        max_inter_observation_time: compiled.max_inter_observation_time(attacker),
+        min_inter_announcement_time: compiled.min_inter_announcement_time,
+        min_lookahead: compiled.min_lookahead,
+        avg_lookahead: Some(compiled.avg_lookahead),
+        verdict: CautiousVerdict::Secured,
     };
+    let a = attacker as usize;
     let mut c: HashSet<TimedCoordinate> = Default::default();
-    for t in 0..solution.statistics.makespan + 1 {
-        // time, agent name, reachable set
-        let mut x: HashMap<usize, HashMap<String, HashSet<Coordinate>>> = Default::default();
+    for t in 0..compiled.makespan + 1 {
+        let horizons = compiled.horizons(attacker, t);
+        // time, agent, reachable set
+        let mut x: HashMap<usize, Vec<HashSet<Coordinate>>> = Default::default();
         let mut s: usize = 0;
-        x.insert(t, Default::default());
-        for agent in &instance.agents {
-            let mut init_set = HashSet::new();
-            init_set.insert(solution.schedule[&agent.name][t].into());
-            x.get_mut(&t).unwrap().insert(agent.name.clone(), init_set);
-        }
-        'outer: while x[&(t + s)][attacker_name]
-            .is_disjoint(&defender_observed(&x[&(t + s)], attacker_name))
-        {
-            //println!("{}: {} + {}", attacker_name, t, s);
-            //print_board(&x[&(t + s)], attacker_name, instance);
+        x.insert(
+            t,
+            compiled
+                .agents()
+                .map(|agent| iter::once(compiled.at(agent, t).into()).collect())
+                .collect(),
+        );
+        'outer: while x[&(t + s)][a].is_disjoint(&defender_observed(&x[&(t + s)], attacker)) {
+            iterations += 1;
+            if budget.exceeded(started, iterations) {
+                res.verdict = CautiousVerdict::Unknown;
+                return res;
+            }
+            CautiousTrace::snapshot(&mut trace, compiled, t, s, &x[&(t + s)], attacker, &c);
             x.insert(t + s + 1, x[&(t + s)].clone());
-            for agent in &instance.agents {
+            for agent in compiled.agents() {
                 let new_flood = match reachable(
-                    solution,
-                    announcements,
+                    compiled,
                     &g,
-                    x.get(&(t + s + 1)).unwrap().get(&agent.name).unwrap(),
-                    t,     // to check what the announcements are
-                    t + s, // to check in the plan
+                    &x[&(t + s + 1)][agent as usize],
+                    &horizons, // to check what the announcements are
+                    t + s,     // to check in the plan
                     &mut c,
                     false,
                 ) {
                     Ok(flood) => flood,
                     Err(_) => {
-                        // println!("found conflict, restarting");
+                        CautiousTrace::record(
+                            &mut trace,
+                            CautiousEvent::Restart {
+                                agent: compiled.name(agent).clone(),
+                            },
+                        );
                         s = 0;
                         continue 'outer;
                     }
                 };
-                x.get_mut(&(t + s + 1))
-                    .unwrap()
-                    .insert(agent.name.clone(), new_flood);
+                x.get_mut(&(t + s + 1)).unwrap()[agent as usize] = if agent == attacker {
+                    affordable(compiled, attacker, t, t + s + 1, new_flood)
+                } else {
+                    new_flood
+                };
             }
-            let diff_defender = x[&(t + s + 1)][attacker_name]
-                .difference(
-                    &x[&(t + s)]
-                        .iter()
-                        .filter(|(name, _)| *name != attacker_name)
-                        .map(|(_, flood)| flood)
-                        .fold(HashSet::new(), |acc, elt| {
-                            acc.union(&elt).cloned().collect()
-                        }),
-                )
+            let diff_defender = x[&(t + s + 1)][a]
+                .difference(&defender_floods(&x[&(t + s)], attacker))
                 .cloned()
                 .collect();
-            x.get_mut(&(t + s + 1))
-                .unwrap()
-                .insert(attacker_name.clone(), diff_defender);
-            for defender in instance
-                .agents
-                .iter()
-                .filter(|agent| agent.name != *attacker_name)
-            {
-                let diff_attacker = x[&(t + s + 1)][&defender.name]
-                    .difference(&x[&(t + s + 1)][attacker_name])
+            x.get_mut(&(t + s + 1)).unwrap()[a] = diff_defender;
+            for defender in compiled.defenders(attacker) {
+                let d = defender as usize;
+                let diff_attacker = x[&(t + s + 1)][d]
+                    .difference(&x[&(t + s + 1)][a])
                     .cloned()
                     .collect();
-                x.get_mut(&(t + s + 1))
-                    .unwrap()
-                    .insert(defender.name.clone(), diff_attacker);
+                x.get_mut(&(t + s + 1)).unwrap()[d] = diff_attacker;
             }
-            let diff_defender_next = x[&(t + s + 1)][attacker_name]
-                .difference(
-                    &x[&(t + s + 1)]
-                        .iter()
-                        .filter(|(name, _)| *name != attacker_name)
-                        .map(|(_, flood)| flood)
-                        .fold(HashSet::new(), |acc, elt| {
-                            acc.union(&elt).cloned().collect()
-                        }),
-                )
+            let diff_defender_next = x[&(t + s + 1)][a]
+                .difference(&defender_floods(&x[&(t + s + 1)], attacker))
                 .cloned()
                 .collect();
-            x.get_mut(&(t + s + 1))
-                .unwrap()
-                .insert(attacker_name.clone(), diff_defender_next);
+            x.get_mut(&(t + s + 1)).unwrap()[a] = diff_defender_next;
             if x[&(t + s)] == x[&(t + s + 1)] {
                 // no progress
-                res.secured = false;
+                CautiousTrace::record(&mut trace, CautiousEvent::Fixpoint);
+                res.verdict = CautiousVerdict::NotSecured;
                 return res;
             }
             s = s + 1;
         }
-        if x[&(t + s)][attacker_name]
-            .intersection(&defender_observed(&x[&(t + s)], attacker_name))
+        CautiousTrace::snapshot(&mut trace, compiled, t, s, &x[&(t + s)], attacker, &c);
+        if x[&(t + s)][a]
+            .intersection(&defender_observed(&x[&(t + s)], attacker))
+            .sorted()
             .take(1) // how many potential observations to check
             .map(|&p| {
-                attack_exists(
-                    solution,
-                    announcements,
-                    attacker_name,
-                    safe,
-                    t,
-                    t + s,
-                    p,
-                    &g,
-                    &x,
-                    &mut c,
-                )
+                let attack = attack_exists(compiled, attacker, safe, t, t + s, p, &g, &x, &mut c);
+                CautiousTrace::record(&mut trace, CautiousEvent::Observable { at: p, attack });
+                attack
             })
             .all(|b| b)
         {
-            res.secured = false;
+            res.verdict = CautiousVerdict::NotSecured;
             return res;
         }
     }
@@ -275,48 +693,30 @@ fn run_cautious_attempt(
 }
 
 fn attack_exists(
-    solution: &MapfSolution,
-    announcements: &Announcements,
-    attacker_name: &String,
+    compiled: &Compiled,
+    attacker: AgentId,
     safe: &Coordinate,
     start_time: usize,
     end_time: usize,
     obs_coord: Coordinate,
     g: &DiGraphMap<TimedCoordinate, ()>,
-    x: &HashMap<usize, HashMap<String, HashSet<Coordinate>>>,
+    x: &HashMap<usize, Vec<HashSet<Coordinate>>>,
     conflicts: &mut HashSet<TimedCoordinate>,
 ) -> bool {
-    let mut a = x[&start_time][attacker_name].clone();
+    let horizons = compiled.horizons(attacker, start_time);
+    let mut a = x[&start_time][attacker as usize].clone();
     let mut b: HashSet<Coordinate> = Default::default();
     for u in start_time..end_time {
-        a = reachable(
-            solution,
-            announcements,
-            g,
-            &a,
-            start_time,
-            u,
-            conflicts,
-            true,
-        )
-        .unwrap();
-        b = reachable(
-            solution,
-            announcements,
-            g,
-            &b,
-            start_time,
-            u,
-            conflicts,
-            true,
-        )
-        .unwrap();
+        a = reachable(compiled, g, &a, &horizons, u, conflicts, true).unwrap();
+        a = affordable(compiled, attacker, start_time, u + 1, a);
+        b = reachable(compiled, g, &b, &horizons, u, conflicts, true).unwrap();
+        b = affordable(compiled, attacker, start_time, u + 1, b);
         a = a
-            .difference(&defender_observed(&x[&u], attacker_name))
+            .difference(&defender_observed(&x[&u], attacker))
             .cloned()
             .collect();
         b = b
-            .difference(&defender_observed(&x[&u], attacker_name))
+            .difference(&defender_observed(&x[&u], attacker))
             .cloned()
             .collect();
         if a.contains(&safe) {
@@ -329,12 +729,29 @@ fn attack_exists(
     b.contains(&obs_coord)
 }
 
+// the cells of `flood` the attacker, on its plan until `t`, can afford to be in at `u`
+fn affordable(
+    compiled: &Compiled,
+    attacker: AgentId,
+    t: usize,
+    u: usize,
+    flood: HashSet<Coordinate>,
+) -> HashSet<Coordinate> {
+    if compiled.energy(attacker).is_none() {
+        return flood;
+    }
+    let (from, spent) = (compiled.at(attacker, t), compiled.drawn(attacker, t));
+    flood
+        .into_iter()
+        .filter(|cell| compiled.affords(attacker, spent, from, cell.as_time(u)))
+        .collect()
+}
+
 fn reachable(
-    solution: &MapfSolution,
-    announcements: &Announcements,
+    compiled: &Compiled,
     g: &DiGraphMap<TimedCoordinate, ()>,
     flood: &HashSet<Coordinate>,
-    curr_time: usize,
+    horizons: &[usize],
     fut_time: usize,
     conflicts: &mut HashSet<TimedCoordinate>,
     attacker_mode: bool,
@@ -344,11 +761,10 @@ fn reachable(
         new_flood = new_flood
             .union(
                 &(match move_robot(
-                    solution,
-                    announcements,
+                    compiled,
                     g,
                     *v,
-                    curr_time,
+                    horizons,
                     fut_time,
                     conflicts,
                     attacker_mode,
@@ -370,20 +786,19 @@ fn reachable(
 }
 
 fn move_robot(
-    solution: &MapfSolution,
-    announcements: &Announcements,
+    compiled: &Compiled,
     g: &DiGraphMap<TimedCoordinate, ()>,
     coord: Coordinate,
-    curr_time: usize,
+    horizons: &[usize],
     fut_time: usize,
     conflicts: &mut HashSet<TimedCoordinate>,
     attacker_mode: bool,
 ) -> Result<HashSet<Coordinate>, ()> {
     let mut res = HashSet::new();
-    if !attacker_mode && fut_time < solution.statistics.makespan {
-        for (name, path) in solution.schedule.iter() {
-            if path[fut_time] == coord.as_time(fut_time)
-                && announcements.schedule[name][curr_time] > fut_time + 1
+    if !attacker_mode && fut_time < compiled.makespan {
+        for agent in compiled.agents() {
+            let path = compiled.path(agent);
+            if path[fut_time] == coord.as_time(fut_time) && horizons[agent as usize] > fut_time + 1
             {
                 res.insert(path[fut_time + 1].into());
                 return Ok(res);
@@ -391,14 +806,15 @@ fn move_robot(
         }
     }
     res = g.neighbors(coord.as_time(1)).map(|tc| tc.into()).collect();
-    if fut_time < solution.statistics.makespan {
-        for (name, path) in solution.schedule.iter() {
-            if announcements.schedule[name][curr_time] > fut_time + 1 {
-                res.remove(&path[fut_time + 1].into());
+    if fut_time < compiled.makespan {
+        for agent in compiled.agents() {
+            if horizons[agent as usize] > fut_time + 1 {
+                res.remove(&compiled.at(agent, fut_time + 1).into());
             }
         }
         if !res.contains(&coord) {
-            for (_, path) in solution.schedule.iter() {
+            for agent in compiled.agents() {
+                let path = compiled.path(agent);
                 if path[fut_time + 1] == coord.as_time(fut_time + 1) {
                     res.remove(&path[fut_time].into());
                 }
@@ -422,45 +838,26 @@ fn move_robot(
     Ok(res)
 }
 
-fn print_board(
-    floods: &HashMap<String, HashSet<Coordinate>>,
-    attacker_name: &String,
-    instance: &MapfInstance,
-) {
-    let mut board = vec![' '; (instance.map.dimensions.x * instance.map.dimensions.y).into()];
-    floods.iter().for_each(|(name, flood)| {
-        for coord in flood {
-            board[(coord.y * instance.map.dimensions.x + coord.x) as usize] =
-                if name == attacker_name { 'A' } else { 'D' }
-        }
-    });
-    instance
-        .map
-        .obstacles
-        .iter()
-        .for_each(|coord| board[(coord.y * instance.map.dimensions.x + coord.x) as usize] = 'X');
-    let colored_board = board
+// union of the defenders' floods, the cells some defender may be in
+fn defender_floods(floods: &[HashSet<Coordinate>], attacker: AgentId) -> HashSet<Coordinate> {
+    floods
         .iter()
-        .map(|x| cell::Cell::new(x.clone(), 50, 0))
-        .collect::<Vec<_>>();
-    let format = Format::new(1, 1);
-    let mut data = matrix::Matrix::new(instance.map.dimensions.x.into(), colored_board);
-    let display = MatrixDisplay::new(&format, &mut data);
-    display.print(&mut std::io::stdout(), &style::BordersStyle::None);
+        .enumerate()
+        .filter(|&(agent, _)| agent != attacker as usize)
+        .map(|(_, flood)| flood)
+        .fold(HashSet::new(), |acc, elt| acc.union(elt).cloned().collect())
 }
 
-fn defender_observed(
-    floods: &HashMap<String, HashSet<Coordinate>>,
-    attacker_name: &String,
-) -> HashSet<Coordinate> {
+fn defender_observed(floods: &[HashSet<Coordinate>], attacker: AgentId) -> HashSet<Coordinate> {
     floods
         .iter()
-        .filter(|(name, _)| *name != attacker_name)
+        .enumerate()
+        .filter(|&(agent, _)| agent != attacker as usize)
         .map(|(_, flood)| {
             let mut observable: HashSet<Coordinate> = HashSet::new();
             for coord in flood {
-                for x in coord.x - 1..coord.x + 2 {
-                    for y in coord.y - 1..coord.y + 2 {
+                for x in coord.x.saturating_sub(1)..coord.x + 2 {
+                    for y in coord.y.saturating_sub(1)..coord.y + 2 {
                         observable.insert(Coordinate { x: x, y: y });
                     }
                 }
@@ -473,318 +870,585 @@ fn defender_observed(
         })
 }
 
+/// How the defenders catch and answer a bold attacker. `mitigation` enables detection from
+/// unexpected or missing co-observations, which `sensing` makes imperfect. With a `response`,
+/// each detection sends responders after the attacker.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BoldDefense<'a> {
+    pub mitigation: bool,
+    pub sensing: Option<&'a SensingModel>,
+    pub response: Option<&'a Response>,
+}
+
+/// Simulates a bold attacker for a sample of attackers and target obstacles in parallel.
 pub fn run_bold_attempts(
     instance: MapfInstance,
     solution: MapfSolution,
     announcements: Announcements,
-    mitigation: bool,
+    channel: Option<&Channel>,
+    defense: &BoldDefense,
 ) -> BoldExperimentResult {
-    let pb = ProgressBar::new(100u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("[{eta:>5}->{elapsed:>5}] [{wide_bar}] {pos:>7}/{len:>7}")
-            .progress_chars("=> "),
-    );
+    let compiled = compile(&instance, &solution, &announcements, channel);
     BoldExperimentResult {
-        attempts: instance
-            .agents
-            .iter()
-            .take(10)
-            .cartesian_product(instance.map.obstacles.iter().take(10))
-            .par_bridge()
-            .progress_with(pb)
-            .map(|(attacker, safe)| {
-                run_bold_attempt(
-                    &instance,
-                    &solution,
-                    &announcements,
-                    &attacker.name,
-                    safe,
-                    mitigation,
-                )
-            })
-            .collect(),
+        provenance: None,
+        attempts: sampled_attempts(&compiled, None, |attacker, safe| {
+            run_bold_attempt(&compiled, attacker, safe, defense, None)
+        })
+        .expect("nothing to record without a checkpoint"),
     }
 }
 
-fn run_bold_attempt(
+/// Like [`run_bold_attempts`], but skips the attempts already in `checkpoint` and records every
+/// new one there as soon as it finishes.
+pub fn resume_bold_attempts(
+    instance: MapfInstance,
+    solution: MapfSolution,
+    announcements: Announcements,
+    channel: Option<&Channel>,
+    defense: &BoldDefense,
+    checkpoint: Checkpoint<BoldAttemptResult>,
+) -> error::Result<BoldExperimentResult> {
+    let compiled = compile(&instance, &solution, &announcements, channel);
+    Ok(BoldExperimentResult {
+        provenance: None,
+        attempts: sampled_attempts(&compiled, Some(checkpoint), |attacker, safe| {
+            run_bold_attempt(&compiled, attacker, safe, defense, None)
+        })?,
+    })
+}
+
+/// Runs a single bold attempt exactly as [`run_bold_attempts`] would, recording where the
+/// attacker went and what was detected at each step.
+pub fn trace_bold_attempt(
     instance: &MapfInstance,
     solution: &MapfSolution,
     announcements: &Announcements,
-    attacker_name: &String,
+    attacker_name: &str,
     safe: &Coordinate,
     mitigation: bool,
+) -> (BoldAttemptResult, BoldTrace) {
+    let compiled = Compiled::new(instance, solution, announcements);
+    let mut trace = BoldTrace::default();
+    let res = run_bold_attempt(
+        &compiled,
+        attacker_id(&compiled, attacker_name),
+        safe,
+        &BoldDefense {
+            mitigation,
+            ..BoldDefense::default()
+        },
+        Some(&mut trace),
+    );
+    (res, trace)
+}
+
+/// Simulates an equivocating bold attacker for a sample of attackers and target obstacles in
+/// parallel. It heads for the target avoiding only collisions and tells its deviation to the
+/// defenders that would notice it, see [`PerRecipient`], so co-observations never give it away.
+/// With `cross_checks`, defenders that co-observe each other compare what they heard and
+/// detect it when that differs.
+pub fn run_equivocating_attempts(
+    instance: MapfInstance,
+    solution: MapfSolution,
+    announcements: Announcements,
+    channel: Option<&Channel>,
+    cross_checks: bool,
+) -> BoldExperimentResult {
+    let compiled = compile(&instance, &solution, &announcements, channel);
+    BoldExperimentResult {
+        provenance: None,
+        attempts: sampled_attempts(&compiled, None, |attacker, safe| {
+            run_equivocating_attempt(&compiled, attacker, safe, cross_checks)
+        })
+        .expect("nothing to record without a checkpoint"),
+    }
+}
+
+/// Like [`run_equivocating_attempts`], but skips the attempts already in `checkpoint` and
+/// records every new one there as soon as it finishes.
+pub fn resume_equivocating_attempts(
+    instance: MapfInstance,
+    solution: MapfSolution,
+    announcements: Announcements,
+    channel: Option<&Channel>,
+    cross_checks: bool,
+    checkpoint: Checkpoint<BoldAttemptResult>,
+) -> error::Result<BoldExperimentResult> {
+    let compiled = compile(&instance, &solution, &announcements, channel);
+    Ok(BoldExperimentResult {
+        provenance: None,
+        attempts: sampled_attempts(&compiled, Some(checkpoint), |attacker, safe| {
+            run_equivocating_attempt(&compiled, attacker, safe, cross_checks)
+        })?,
+    })
+}
+
+fn run_equivocating_attempt(
+    compiled: &Compiled,
+    attacker: AgentId,
+    safe: &Coordinate,
+    cross_checks: bool,
 ) -> BoldAttemptResult {
-    let mut g = build_graph(&instance, &solution, safe);
-    let mut attacker_pos = solution.schedule[attacker_name][0];
+    let mut g = build_graph(compiled, safe);
+    let mut attacker_pos = compiled.at(attacker, 0);
     let mut deviation: Vec<TimedCoordinate> = Vec::new();
-    let mut res = BoldAttemptResult {
-        attacker_name: attacker_name.clone(),
+    let mut reached_at = None;
+    let mut res = undetected(compiled, attacker, safe);
+    let mut spent = 0.0;
+    for t in 0..compiled.makespan + 1 {
+        deviation.push(attacker_pos);
+        if !deviation_valid(compiled, attacker, &deviation)
+            || drained(compiled, attacker, &deviation, &mut spent)
+        {
+            // caused a collision or ran its battery down
+            res.detected = true;
+            break;
+        }
+        let nominal = compiled.at(attacker, t);
+        res.max_deviated_dist = max(res.max_deviated_dist, attacker_pos.manh_dist(&nominal));
+        // only collisions constrain the walk, the lies cover the co-observations
+        prune_graph(&mut g, compiled, attacker, t, false);
+        prune_energy(&mut g, compiled, attacker, attacker_pos, spent);
+        if Coordinate::from(attacker_pos) == *safe && !res.dangerous {
+            reached_at = Some(t);
+            res.dangerous = true;
+        }
+        attacker_pos = if res.dangerous || !affords_target(&g, compiled, attacker, safe, t) {
+            step_to_nominal(&g, compiled, attacker, attacker_pos, t)
+        } else {
+            step_to_safe(&g, attacker_pos, safe, t)
+        };
+    }
+    let told = PerRecipient::new(compiled, attacker, &deviation);
+    let caught_at = if cross_checks {
+        told.cross_check(compiled, attacker)
+    } else {
+        None
+    };
+    res.detected |= caught_at.is_some();
+    res.equivocation = Some(EquivocationOutcome {
+        recipients: told.recipients(),
+        reached_at,
+        caught_at,
+    });
+    res
+}
+
+fn run_bold_attempt(
+    compiled: &Compiled,
+    attacker: AgentId,
+    safe: &Coordinate,
+    defense: &BoldDefense,
+    trace: Option<&mut BoldTrace>,
+) -> BoldAttemptResult {
+    let mut chances = DetectionChances {
+        defenders: compiled.defenders(attacker).collect(),
+        ..DetectionChances::default()
+    };
+    let mut walk = Walk::default();
+    let mut res = bold_walk(
+        compiled,
+        attacker,
+        safe,
+        defense.mitigation,
+        &mut chances,
+        &mut walk,
+        trace,
+    );
+    res.sensed = defense
+        .sensing
+        .map(|model| model.sample(&chances, attacker, safe));
+    res.response = defense.response.map(|response| {
+        response.contain(
+            compiled,
+            attacker,
+            safe,
+            &walk.positions,
+            walk.detected_at,
+            walk.reached_at,
+        )
+    });
+    res
+}
+
+// where the bold attacker went, from time step 0, and when it was first detected and reached
+// the target
+#[derive(Default)]
+struct Walk {
+    positions: Vec<TimedCoordinate>,
+    detected_at: Option<usize>,
+    reached_at: Option<usize>,
+}
+
+// an attempt that has not reached the target nor been detected yet
+fn undetected(compiled: &Compiled, attacker: AgentId, safe: &Coordinate) -> BoldAttemptResult {
+    BoldAttemptResult {
+        attacker_name: compiled.name(attacker).clone(),
         safe: *safe,
         dangerous: false,
         detected: false,
         max_deviated_dist: 0,
-        max_inter_observation_time: solution.max_inter_observation_time(&attacker_name),
-        min_inter_announcement_time: announcements.min_inter_announcement_time(),
-        min_lookahead: announcements.min_lookahead(),
-        avg_lookahead: Some(announcements.avg_lookahead()),
-    };
-    for t in 0..solution.statistics.makespan + 1 {
+        max_inter_observation_time: compiled.max_inter_observation_time(attacker),
+        min_inter_announcement_time: compiled.min_inter_announcement_time,
+        min_lookahead: compiled.min_lookahead,
+        avg_lookahead: Some(compiled.avg_lookahead),
+        sensed: None,
+        equivocation: None,
+        response: None,
+    }
+}
+
+// the bold attacker's walk with perfect sensing, counting what the defenders could have noticed
+// on the way in `chances` and where it went in `walk`
+fn bold_walk(
+    compiled: &Compiled,
+    attacker: AgentId,
+    safe: &Coordinate,
+    mitigation: bool,
+    chances: &mut DetectionChances,
+    walk: &mut Walk,
+    mut trace: Option<&mut BoldTrace>,
+) -> BoldAttemptResult {
+    let mut g = build_graph(compiled, safe);
+    let nominal = compiled.path(attacker);
+    let mut attacker_pos = nominal[0];
+    let deviation = &mut walk.positions;
+    let mut res = undetected(compiled, attacker, safe);
+    let mut spent = 0.0;
+    for t in 0..compiled.makespan + 1 {
         deviation.push(attacker_pos);
-        let mut dev_sol = solution.clone();
-        dev_sol.statistics.makespan = t;
-        dev_sol
-            .schedule
-            .insert(attacker_name.clone(), deviation.clone());
-        dev_sol
-            .schedule
-            .iter_mut()
-            .for_each(|(_, sched)| sched.truncate(t + 1));
-        if !dev_sol.valid(instance) {
+        if let Some(trace) = trace.as_mut() {
+            trace.steps.push(BoldStep {
+                t,
+                position: attacker_pos,
+                events: Vec::new(),
+            });
+        }
+        if !deviation_valid(compiled, attacker, deviation) {
             // caused a collision
             println!("failed: caused collision!");
+            BoldTrace::record(&mut trace, BoldEvent::Collision);
+            chances.certain = true;
             res.detected = true;
+            walk.detected_at.get_or_insert(t);
             return res;
         }
-        res.max_deviated_dist = max(
-            res.max_deviated_dist,
-            attacker_pos.manh_dist(&solution.schedule[attacker_name][t]),
-        );
+        if drained(compiled, attacker, deviation, &mut spent) {
+            BoldTrace::record(&mut trace, BoldEvent::Drained);
+            chances.certain = true;
+            res.detected = true;
+            walk.detected_at.get_or_insert(t);
+            return res;
+        }
+        res.max_deviated_dist = max(res.max_deviated_dist, attacker_pos.manh_dist(&nominal[t]));
         // update the graph from the announcement
-        prune_graph(
-            &mut g,
-            &instance,
-            &solution,
-            &attacker_name,
-            &announcements,
-            t,
-            mitigation,
-        );
-        if mitigation && !g.contains_node(attacker_pos) {
+        prune_graph(&mut g, compiled, attacker, t, mitigation);
+        prune_energy(&mut g, compiled, attacker, attacker_pos, spent);
+        let unexpected = mitigation && !g.contains_node(attacker_pos);
+        // only defenders that heard where the attacker should be can tell it is elsewhere
+        let knows = |defender| compiled.knows_step(defender, attacker, t);
+        let sees = |defender| compiled.at(defender, t).adj(&attacker_pos);
+        let noticed = unexpected
+            && (compiled.defenders(attacker).all(|defender| !sees(defender))
+                || compiled
+                    .defenders(attacker)
+                    .any(|defender| sees(defender) && knows(defender)));
+        if noticed {
             println!("failed: unexpected observations!");
+            BoldTrace::record(&mut trace, BoldEvent::UnexpectedObservation);
             res.detected = true;
+            walk.detected_at.get_or_insert(t);
         }
         if mitigation {
-            for agent in &instance.agents {
-                if agent.name != *attacker_name {
-                    if solution.schedule[&agent.name][t].adj(&solution.schedule[attacker_name][t])
-                        && (attacker_pos != solution.schedule[attacker_name][t])
-                    {
-                        println!(
-                            "failed: missed observation! was {:?} but should be {:?}",
-                            attacker_pos, solution.schedule[attacker_name][t]
-                        );
-                        res.detected = true;
-                    }
+            let mut witnesses = Witnesses::default();
+            let mut seen = false;
+            for defender in compiled.defenders(attacker) {
+                let position = compiled.at(defender, t);
+                let missed =
+                    knows(defender) && position.adj(&nominal[t]) && (attacker_pos != nominal[t]);
+                if missed {
+                    println!(
+                        "failed: missed observation! was {:?} but should be {:?}",
+                        attacker_pos, nominal[t]
+                    );
+                    BoldTrace::record(
+                        &mut trace,
+                        BoldEvent::MissedObservation {
+                            observer: compiled.name(defender).clone(),
+                        },
+                    );
+                    res.detected = true;
+                    walk.detected_at.get_or_insert(t);
                 }
+                let sees = sees(defender);
+                if missed || (unexpected && sees && knows(defender)) {
+                    witnesses.deviation.push(defender);
+                } else if attacker_pos == nominal[t] && sees && knows(defender) {
+                    witnesses.conforming.push(defender);
+                }
+                seen |= unexpected && sees;
+            }
+            // edges into removed nodes survive remove_node, so the walk can step onto an
+            // obstacle, an unexpected observation without any defender nearby
+            chances.certain |= unexpected && !seen;
+            if !witnesses.is_empty() {
+                chances.steps.push(witnesses);
             }
         }
         if Coordinate::from(attacker_pos) == *safe {
+            if !res.dangerous {
+                BoldTrace::record(&mut trace, BoldEvent::ReachedTarget);
+                walk.reached_at = Some(t);
+            }
             res.dangerous = true;
         }
         if res.dangerous {
             // go to nominal
-            let path_to_nominal = astar(
-                &g,
-                attacker_pos,
-                |finish| {
-                    (finish.t > t)
-                        && (finish.t < announcements.schedule[attacker_name][t])
-                        && (finish == solution.schedule[attacker_name][finish.t])
-                },
-                |_| 1,
-                |n| {
-                    if n.t < announcements.schedule[attacker_name][t] {
-                        n.manh_dist(&solution.schedule[attacker_name][n.t]) / 2 // nominal and deviation head towards each other
-                    } else {
-                        0
-                    }
-                },
-            );
-            attacker_pos = match path_to_nominal {
-                Some((_, path)) => path[1],
-                None => g
-                    .neighbors(attacker_pos)
-                    .next()
-                    .unwrap_or(attacker_pos.as_time(t)),
-            };
+            attacker_pos = step_to_nominal(&g, compiled, attacker, attacker_pos, t);
         } else {
             // go to safe, meeting known observation constraints
-            attacker_pos =
-                match next_observed(&instance, &solution, &attacker_name, &announcements, t) {
-                    Some(obs) => {
-                        // let's check the shortest path to safe, maybe we know a path that still
-                        // meets the obs
-                        let mut found_known_dev = false;
-                        let mut inter = attacker_pos.clone();
-                        let mut known_dev_step = attacker_pos.clone();
-                        let full_information = announcements
-                            .schedule
-                            .iter()
-                            .map(|(_, sched)| sched[t])
-                            .fold(true, |acc, elt| acc && (elt > obs.t));
-                        while inter.t < obs.t && !found_known_dev && full_information {
-                            let path_to_safe = astar(
-                                &g,
-                                attacker_pos,
-                                |finish| finish.t > inter.t && Coordinate::from(finish) == *safe,
-                                |_| 1,
-                                |n| safe.manh_dist(&Coordinate::from(n)),
-                            );
-                            inter = match path_to_safe {
-                                Some((_, ref path)) => *path.last().unwrap(),
-                                None => break,
-                            };
-                            let path_from_inter_to_nominal = astar(
-                                &g,
-                                inter,
-                                |finish| {
-                                    (finish.t <= obs.t)
-                                        && (finish.t < announcements.schedule[attacker_name][t])
-                                        && (finish == solution.schedule[attacker_name][finish.t])
-                                },
-                                |_| 1,
-                                |n| {
-                                    if n.t < announcements.schedule[attacker_name][t] {
-                                        n.manh_dist(&solution.schedule[attacker_name][n.t]) / 2
-                                    } else {
-                                        0
-                                    }
-                                },
-                            );
-                            found_known_dev = match path_from_inter_to_nominal {
-                                Some(_) => {
-                                    known_dev_step = path_to_safe.unwrap().1[1].clone();
-                                    true
+            attacker_pos = match next_observed(compiled, attacker, t) {
+                Some(obs) => {
+                    // let's check the shortest path to safe, maybe we know a path that still
+                    // meets the obs
+                    let mut found_known_dev = false;
+                    let mut inter = attacker_pos;
+                    let mut known_dev = Vec::new();
+                    let full_information = compiled
+                        .agents()
+                        .map(|agent| compiled.known(attacker, agent, t))
+                        .all(|known| known > obs.t);
+                    while inter.t < obs.t && !found_known_dev && full_information {
+                        let path_to_safe = astar(
+                            &g,
+                            attacker_pos,
+                            |finish| finish.t > inter.t && Coordinate::from(finish) == *safe,
+                            |_| 1,
+                            |n| safe.manh_dist(&Coordinate::from(n)),
+                        );
+                        inter = match path_to_safe {
+                            Some((_, ref path)) => *path.last().unwrap(),
+                            None => break,
+                        };
+                        let path_from_inter_to_nominal = astar(
+                            &g,
+                            inter,
+                            |finish| {
+                                (finish.t <= obs.t)
+                                    && (finish.t < compiled.announced(attacker, t))
+                                    && (finish == nominal[finish.t])
+                            },
+                            |_| 1,
+                            |n| {
+                                if n.t < compiled.announced(attacker, t) {
+                                    n.manh_dist(&nominal[n.t]) / 2
+                                } else {
+                                    0
                                 }
-                                None => false,
-                            }
-                        }
-                        if found_known_dev {
-                            // can I return early here? let's try it
-                            res.dangerous = true;
-                            return res;
-                        } else {
-                            // this just goes to nominal
-                            let path_to_nominal = astar(
-                                &g,
-                                attacker_pos,
-                                |finish| {
-                                    (finish.t > t)
-                                        && (finish.t < announcements.schedule[attacker_name][t])
-                                        && (finish == solution.schedule[attacker_name][finish.t])
-                                },
-                                |_| 1,
-                                |n| {
-                                    if n.t < announcements.schedule[attacker_name][t] {
-                                        n.manh_dist(&solution.schedule[attacker_name][n.t]) / 2
-                                    // nominal and deviation head towards each other
-                                    } else {
-                                        0
-                                    }
-                                },
-                            );
-                            match path_to_nominal {
-                                Some((_, path)) => path[1],
-                                None => g
-                                    .neighbors(attacker_pos)
-                                    .next()
-                                    .unwrap_or(attacker_pos.as_time(t)),
+                            },
+                        );
+                        found_known_dev = match path_from_inter_to_nominal {
+                            Some(_) => {
+                                known_dev = path_to_safe.unwrap().1;
+                                true
                             }
+                            None => false,
                         }
                     }
-                    None => {
-                        let mut found_known_dev = false;
-                        let mut inter = attacker_pos.clone();
-                        let mut known_dev_step = attacker_pos.clone();
-                        let known_horizon = announcements
-                            .schedule
-                            .iter()
-                            .map(|(_, sched)| sched[t])
-                            .min()
-                            .unwrap();
-                        while inter.t < known_horizon && !found_known_dev {
-                            let path_to_safe = astar(
-                                &g,
-                                attacker_pos,
-                                |finish| finish.t > inter.t && Coordinate::from(finish) == *safe,
-                                |_| 1,
-                                |n| safe.manh_dist(&Coordinate::from(n)),
-                            );
-                            inter = match path_to_safe {
-                                Some((_, ref path)) => *path.last().unwrap(),
-                                None => break,
-                            };
-                            let path_from_inter_to_nominal = astar(
-                                &g,
-                                inter,
-                                |finish| {
-                                    (finish.t <= known_horizon)
-                                        && (finish.t < announcements.schedule[attacker_name][t])
-                                        && (finish == solution.schedule[attacker_name][finish.t])
-                                },
-                                |_| 1,
-                                |n| {
-                                    if n.t < announcements.schedule[attacker_name][t] {
-                                        n.manh_dist(&solution.schedule[attacker_name][n.t]) / 2
-                                    } else {
-                                        0
-                                    }
-                                },
-                            );
-                            found_known_dev = match path_from_inter_to_nominal {
-                                Some(_) => {
-                                    known_dev_step = path_to_safe.unwrap().1[1].clone();
-                                    true
-                                }
-                                None => false,
-                            }
-                        }
-                        if found_known_dev {
-                            res.dangerous = true;
-                            return res;
-                        }
-                        // go to safe
+                    if found_known_dev {
+                        // can I return early here? let's try it
+                        BoldTrace::record(&mut trace, BoldEvent::KnownDeviation);
+                        res.dangerous = true;
+                        walk.reached_at = known_dev.last().map(|reached| reached.t);
+                        deviation.extend_from_slice(&known_dev[1..]);
+                        return res;
+                    } else {
+                        // this just goes to nominal
+                        step_to_nominal(&g, compiled, attacker, attacker_pos, t)
+                    }
+                }
+                None => {
+                    let mut found_known_dev = false;
+                    let mut inter = attacker_pos;
+                    let mut known_dev = Vec::new();
+                    let known_horizon = compiled
+                        .agents()
+                        .map(|agent| compiled.known(attacker, agent, t))
+                        .min()
+                        .unwrap();
+                    while inter.t < known_horizon && !found_known_dev {
                         let path_to_safe = astar(
                             &g,
                             attacker_pos,
-                            |finish| Coordinate::from(finish) == *safe,
+                            |finish| finish.t > inter.t && Coordinate::from(finish) == *safe,
                             |_| 1,
                             |n| safe.manh_dist(&Coordinate::from(n)),
                         );
-                        match path_to_safe {
-                            Some((_, path)) => path[1],
-                            None => g
-                                .neighbors(attacker_pos)
-                                .next()
-                                .unwrap_or(attacker_pos.as_time(t)),
+                        inter = match path_to_safe {
+                            Some((_, ref path)) => *path.last().unwrap(),
+                            None => break,
+                        };
+                        let path_from_inter_to_nominal = astar(
+                            &g,
+                            inter,
+                            |finish| {
+                                (finish.t <= known_horizon)
+                                    && (finish.t < compiled.announced(attacker, t))
+                                    && (finish == nominal[finish.t])
+                            },
+                            |_| 1,
+                            |n| {
+                                if n.t < compiled.announced(attacker, t) {
+                                    n.manh_dist(&nominal[n.t]) / 2
+                                } else {
+                                    0
+                                }
+                            },
+                        );
+                        found_known_dev = match path_from_inter_to_nominal {
+                            Some(_) => {
+                                known_dev = path_to_safe.unwrap().1;
+                                true
+                            }
+                            None => false,
                         }
                     }
-                };
+                    if found_known_dev {
+                        BoldTrace::record(&mut trace, BoldEvent::KnownDeviation);
+                        res.dangerous = true;
+                        walk.reached_at = known_dev.last().map(|reached| reached.t);
+                        deviation.extend_from_slice(&known_dev[1..]);
+                        return res;
+                    }
+                    if affords_target(&g, compiled, attacker, safe, t) {
+                        // go to safe
+                        step_to_safe(&g, attacker_pos, safe, t)
+                    } else {
+                        step_to_nominal(&g, compiled, attacker, attacker_pos, t)
+                    }
+                }
+            };
         }
     }
     res
 }
 
-fn next_observed(
-    instance: &MapfInstance,
-    solution: &MapfSolution,
-    attacker_name: &String,
-    announcements: &Announcements,
-    curr_t: usize,
-) -> Option<TimedCoordinate> {
-    for t in (curr_t + 1)
-        ..min(
-            solution.statistics.makespan + 2,
-            announcements.schedule[attacker_name][curr_t],
-        )
+// the next step of the shortest path back onto the attacker's plan within what it announced by
+// `t`, or any step if there is none
+fn step_to_nominal(
+    g: &DiGraphMap<TimedCoordinate, ()>,
+    compiled: &Compiled,
+    attacker: AgentId,
+    attacker_pos: TimedCoordinate,
+    t: usize,
+) -> TimedCoordinate {
+    let nominal = compiled.path(attacker);
+    let path_to_nominal = astar(
+        g,
+        attacker_pos,
+        |finish| {
+            (finish.t > t)
+                && (finish.t < compiled.announced(attacker, t))
+                && (finish == nominal[finish.t])
+        },
+        |_| 1,
+        |n| {
+            if n.t < compiled.announced(attacker, t) {
+                n.manh_dist(&nominal[n.t]) / 2 // nominal and deviation head towards each other
+            } else {
+                0
+            }
+        },
+    );
+    match path_to_nominal {
+        Some((_, path)) => path[1],
+        None => g
+            .neighbors(attacker_pos)
+            .next()
+            .unwrap_or(attacker_pos.as_time(t)),
+    }
+}
+
+// whether the attacker's battery still lets it reach `safe` after `t`, as far as `prune_energy`
+// left it in `g`
+fn affords_target(
+    g: &DiGraphMap<TimedCoordinate, ()>,
+    compiled: &Compiled,
+    attacker: AgentId,
+    safe: &Coordinate,
+    t: usize,
+) -> bool {
+    compiled.energy(attacker).is_none()
+        || (t + 1..compiled.makespan + 1).any(|u| g.contains_node(safe.as_time(u)))
+}
+
+// the next step of the shortest path to `safe`, or any step if there is none
+fn step_to_safe(
+    g: &DiGraphMap<TimedCoordinate, ()>,
+    attacker_pos: TimedCoordinate,
+    safe: &Coordinate,
+    t: usize,
+) -> TimedCoordinate {
+    let path_to_safe = astar(
+        g,
+        attacker_pos,
+        |finish| Coordinate::from(finish) == *safe,
+        |_| 1,
+        |n| safe.manh_dist(&Coordinate::from(n)),
+    );
+    match path_to_safe {
+        Some((_, path)) => path[1],
+        None => g
+            .neighbors(attacker_pos)
+            .next()
+            .unwrap_or(attacker_pos.as_time(t)),
+    }
+}
+
+// whether `MapfSolution::valid` accepts the plan with the attacker following `deviation`,
+// truncated to its last step, given it accepted it one step earlier
+fn deviation_valid(compiled: &Compiled, attacker: AgentId, deviation: &[TimedCoordinate]) -> bool {
+    let t = deviation.len() - 1;
+    let at = |agent: AgentId, t: usize| {
+        if agent == attacker {
+            deviation[t]
+        } else {
+            compiled.at(agent, t)
+        }
+    };
+    let pos: HashSet<Coordinate> = compiled.agents().map(|agent| at(agent, t).into()).collect();
+    if pos.len() != compiled.agent_count() {
+        return false;
+    } // vertex conflict
+    if pos
+        .iter()
+        .any(|coord| coord.x >= compiled.dimensions.x || coord.y >= compiled.dimensions.y)
     {
-        for agent in &instance.agents {
-            if (agent.name != *attacker_name)
-                && (t < announcements.schedule[&agent.name][curr_t])
-                && solution.schedule[&agent.name][t].adj(&solution.schedule[attacker_name][t])
+        return false;
+    } // off the map
+    if t > 0 {
+        for a in compiled.agents() {
+            if at(a, t - 1).manh_dist(&at(a, t)) > 1 {
+                return false; // dynamics constraint
+            }
+            for b in compiled.agents() {
+                if a != b && at(a, t - 1) == at(b, t) && at(b, t - 1) == at(a, t) {
+                    return false; // edge conflict
+                }
+            }
+        }
+    }
+    true
+}
+
+fn next_observed(compiled: &Compiled, attacker: AgentId, curr_t: usize) -> Option<TimedCoordinate> {
+    for t in (curr_t + 1)..min(compiled.makespan + 2, compiled.announced(attacker, curr_t)) {
+        let nominal = compiled.at(attacker, t);
+        for defender in compiled.defenders(attacker) {
+            if (t < compiled.known(attacker, defender, curr_t))
+                && compiled.at(defender, t).adj(&nominal)
             {
-                return Some(solution.schedule[attacker_name][t]);
+                return Some(nominal);
             }
         }
     }
@@ -793,35 +1457,26 @@ fn next_observed(
 
 fn prune_graph(
     g: &mut DiGraphMap<TimedCoordinate, ()>,
-    instance: &MapfInstance,
-    solution: &MapfSolution,
-    attacker_name: &String,
-    announcements: &Announcements,
+    compiled: &Compiled,
+    attacker: AgentId,
     curr_t: usize,
     mitigation: bool,
 ) {
-    for agent in &instance.agents {
-        if agent.name != *attacker_name {
+    for agent in compiled.agents() {
+        if agent != attacker {
             for t in 1..min(
-                solution.statistics.makespan + 2,
-                announcements.schedule[&agent.name][curr_t],
+                compiled.makespan + 2,
+                compiled.known(attacker, agent, curr_t),
             ) {
-                let attacker_pos_nominal = solution.schedule[attacker_name][t];
-                let prev_occupied = solution.schedule[&agent.name][t - 1];
-                let occupied = solution.schedule[&agent.name][t];
+                let attacker_pos_nominal = compiled.at(attacker, t);
+                let prev_occupied = compiled.at(agent, t - 1);
+                let occupied = compiled.at(agent, t);
                 g.remove_edge(occupied.as_time(t - 1), prev_occupied.as_time(t));
                 g.remove_node(occupied);
-                for n_x in (occupied.x - 1)..(occupied.x + 2) {
-                    for n_y in (occupied.y - 1)..(occupied.y + 2) {
-                        let observed = TimedCoordinate {
-                            x: n_x,
-                            y: n_y,
-                            t: t,
-                        };
-                        if mitigation
-                            && observed.adj(&occupied)
-                            && (observed != attacker_pos_nominal)
-                        {
+                if mitigation {
+                    for observed in compiled.neighborhood(occupied.into()) {
+                        let observed = observed.as_time(t);
+                        if observed != attacker_pos_nominal {
                             g.remove_node(observed);
                         }
                     }
@@ -831,36 +1486,69 @@ fn prune_graph(
     }
 }
 
-fn build_graph(
-    instance: &MapfInstance,
-    solution: &MapfSolution,
-    safe: &Coordinate,
-) -> DiGraphMap<TimedCoordinate, ()> {
+// adds the last step of `deviation` to what the attacker has `spent` of its battery, and tells
+// whether it is left unable to finish its plan
+fn drained(
+    compiled: &Compiled,
+    attacker: AgentId,
+    deviation: &[TimedCoordinate],
+    spent: &mut f64,
+) -> bool {
+    let energy = match compiled.energy(attacker) {
+        Some(energy) => energy,
+        None => return false,
+    };
+    let position = *deviation.last().unwrap();
+    if let [.., previous, _] = deviation {
+        *spent += energy.step((*previous).into(), position.into());
+    }
+    !compiled.affords(attacker, *spent, position, position)
+}
+
+// removes the later nodes the attacker, at `position` having `spent` that much of its battery,
+// cannot afford to go through
+fn prune_energy(
+    g: &mut DiGraphMap<TimedCoordinate, ()>,
+    compiled: &Compiled,
+    attacker: AgentId,
+    position: TimedCoordinate,
+    spent: f64,
+) {
+    if compiled.energy(attacker).is_none() {
+        return;
+    }
+    let unaffordable: Vec<TimedCoordinate> = g
+        .nodes()
+        .filter(|&n| n.t > position.t && !compiled.affords(attacker, spent, position, n))
+        .collect();
+    for n in unaffordable {
+        // remove_node leaves the edges into `n` behind, which the walk would still follow
+        let sources: Vec<TimedCoordinate> = g.neighbors_directed(n, Incoming).collect();
+        for source in sources {
+            g.remove_edge(source, n);
+        }
+        g.remove_node(n);
+    }
+}
+
+fn build_graph(compiled: &Compiled, safe: &Coordinate) -> DiGraphMap<TimedCoordinate, ()> {
+    let dimensions = compiled.dimensions;
     let mut g = DiGraphMap::<TimedCoordinate, ()>::default();
-    for t in 0..solution.statistics.makespan + 1 {
-        for x in 0..instance.map.dimensions.x {
-            for y in 0..instance.map.dimensions.y {
+    for t in 0..compiled.makespan + 1 {
+        for x in 0..dimensions.x {
+            for y in 0..dimensions.y {
                 let dest = TimedCoordinate { x: x, y: y, t: t };
                 g.add_node(dest);
                 if t > 0 {
-                    for n_x in (x - 1)..min(instance.map.dimensions.x, x + 2) {
-                        for n_y in (y - 1)..min(instance.map.dimensions.y, y + 2) {
-                            let source = TimedCoordinate {
-                                x: n_x,
-                                y: n_y,
-                                t: t - 1,
-                            };
-                            if source.adj(&dest) {
-                                g.add_edge(source, dest, ());
-                            }
-                        }
+                    for source in compiled.neighborhood(dest.into()) {
+                        g.add_edge(source.as_time(t - 1), dest, ());
                     }
                 }
             }
         }
     }
-    for t in 0..solution.statistics.makespan + 1 {
-        for obstacle in &instance.map.obstacles {
+    for t in 0..compiled.makespan + 1 {
+        for obstacle in &compiled.obstacles {
             if obstacle != safe {
                 g.remove_node(TimedCoordinate {
                     x: obstacle.x,
@@ -872,3 +1560,173 @@ fn build_graph(
     }
     g
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planner::plan_prioritized;
+    use crate::utils::{compute_kahead_announcements, extend_stay_in_place};
+
+    const INSTANCE: &str = "
+map:
+  dimensions: [10, 8]
+  obstacles: [[4, 3], [5, 3], [4, 4], [7, 5], [2, 6], [8, 0]]
+agents:
+  - {name: agent0, start: [1, 1], goal: [8, 6]}
+  - {name: agent1, start: [8, 1], goal: [1, 6]}
+  - {name: agent2, start: [1, 5], goal: [8, 2]}
+";
+
+    // a fresh parse of everything per run, as in separate processes
+    fn inputs() -> (MapfInstance, MapfSolution, Announcements) {
+        let instance: MapfInstance = serde_yaml::from_str(INSTANCE).unwrap();
+        let mut solution = plan_prioritized(&instance).unwrap();
+        extend_stay_in_place(&mut solution);
+        let announcements =
+            compute_kahead_announcements(&instance.agents, 3, solution.statistics.makespan);
+        (instance, solution, announcements)
+    }
+
+    fn bold_yaml() -> String {
+        let (instance, solution, announcements) = inputs();
+        let defense = BoldDefense {
+            mitigation: true,
+            ..BoldDefense::default()
+        };
+        let res = run_bold_attempts(instance, solution, announcements, None, &defense);
+        serde_yaml::to_string(&res).unwrap()
+    }
+
+    fn cautious_yaml() -> String {
+        let (instance, solution, announcements) = inputs();
+        let res = run_cautious_analysis(
+            instance,
+            solution,
+            announcements,
+            None,
+            &CautiousBudget::default(),
+        );
+        serde_yaml::to_string(&res).unwrap()
+    }
+
+    #[test]
+    fn cautious_attempts_out_of_budget_are_unknown() {
+        let run = |budget: &CautiousBudget| {
+            let (instance, solution, announcements) = inputs();
+            run_cautious_analysis(instance, solution, announcements, None, budget)
+        };
+        let unlimited = run(&CautiousBudget::default());
+        assert_eq!(unlimited.unknown_count(), 0);
+        assert!(unlimited.secure_rate().is_some());
+
+        let starved = run(&CautiousBudget {
+            iterations: Some(1),
+            ..CautiousBudget::default()
+        });
+        assert_eq!(starved.attempts.len(), unlimited.attempts.len());
+        assert_eq!(starved.unknown_count(), starved.attempts.len());
+        assert_eq!(starved.unknown_rate(), Some(1.0));
+        // nothing decided, so no secure rate rather than NaN
+        assert_eq!(starved.secure_rate(), None);
+    }
+
+    #[test]
+    fn row_and_column_zero_can_be_entered_and_observed() {
+        // the target sits in column 0, right above the attacker's start
+        let instance: MapfInstance = serde_yaml::from_str(
+            "
+map: {dimensions: [5, 3], obstacles: [[0, 1]]}
+agents:
+  - {name: agent0, start: [0, 0], goal: [4, 0]}
+  - {name: agent1, start: [4, 2], goal: [4, 2]}
+",
+        )
+        .unwrap();
+        let mut solution = plan_prioritized(&instance).unwrap();
+        extend_stay_in_place(&mut solution);
+        let announcements =
+            compute_kahead_announcements(&instance.agents, 1, solution.statistics.makespan);
+        let compiled = Compiled::new(&instance, &solution, &announcements);
+
+        let corner = TimedCoordinate { x: 0, y: 0, t: 1 };
+        let g = build_graph(&compiled, &Coordinate { x: 0, y: 1 });
+        let sources: HashSet<(u16, u16)> = g
+            .neighbors_directed(corner, Incoming)
+            .map(|tc| (tc.x, tc.y))
+            .collect();
+        assert_eq!(sources, vec![(0, 0), (1, 0), (0, 1)].into_iter().collect());
+        let flood: HashSet<Coordinate> = vec![Coordinate { x: 0, y: 0 }].into_iter().collect();
+        assert_eq!(defender_observed(&[HashSet::new(), flood], 0).len(), 4);
+
+        let res = run_bold_attempts(
+            instance,
+            solution,
+            announcements,
+            None,
+            &BoldDefense::default(),
+        );
+        let attempt = &res.attempts[0];
+        assert_eq!(
+            (attempt.attacker_name.as_str(), attempt.safe),
+            ("agent0", Coordinate { x: 0, y: 1 })
+        );
+        assert!(attempt.dangerous);
+    }
+
+    #[test]
+    fn sampled_targets_spread_over_the_map() {
+        // four obstacles in every row of a 10 by 10 map
+        let obstacles: Vec<String> = (0..10)
+            .flat_map(|y| (0..4).map(move |x| format!("[{}, {}]", 2 * x + 1, y)))
+            .collect();
+        let instance: MapfInstance = serde_yaml::from_str(&format!(
+            "
+map: {{dimensions: [10, 10], obstacles: [{}]}}
+agents:
+  - {{name: agent0, start: [0, 0], goal: [0, 9]}}
+",
+            obstacles.join(", ")
+        ))
+        .unwrap();
+        let mut solution = plan_prioritized(&instance).unwrap();
+        extend_stay_in_place(&mut solution);
+        let announcements =
+            compute_kahead_announcements(&instance.agents, 1, solution.statistics.makespan);
+        let compiled = Compiled::new(&instance, &solution, &announcements);
+        let rows: Vec<u16> = sampled_pairs(&compiled)
+            .iter()
+            .map(|(_, safe)| safe.y)
+            .collect();
+        assert_eq!(rows, (0..10).collect::<Vec<u16>>());
+    }
+
+    #[test]
+    fn reruns_and_resumed_runs_write_identical_results() {
+        let bold = bold_yaml();
+        assert_eq!(bold, bold_yaml());
+        let cautious = cautious_yaml();
+        assert_eq!(cautious, cautious_yaml());
+
+        // half of the attempts finished, in reverse order, before the run was killed
+        let path = std::env::temp_dir().join(format!("determinism-{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let done: CautiousExperimentResult = serde_yaml::from_str(&cautious).unwrap();
+        let checkpoint = Checkpoint::create(path, &"test").unwrap();
+        for attempt in done.attempts.iter().rev().step_by(2) {
+            checkpoint.record(attempt).unwrap();
+        }
+        drop(checkpoint);
+        let (instance, solution, announcements) = inputs();
+        let resumed = resume_cautious_analysis(
+            instance,
+            solution,
+            announcements,
+            None,
+            &CautiousBudget::default(),
+            Checkpoint::resume(path, &"test").unwrap(),
+        )
+        .unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(serde_yaml::to_string(&resumed).unwrap(), cautious);
+    }
+}
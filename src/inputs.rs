@@ -1,10 +1,16 @@
+//! MAPF instance, solution and announcement types, in the YAML formats read from disk.
+
 use decorum::N64;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::cmp::{max, min};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
 
-#[derive(Debug, Hash, Eq, Copy, Clone, PartialEq, Serialize, Deserialize)]
+use crate::energy::Energy;
+
+/// A grid cell.
+#[derive(Debug, Hash, Eq, Copy, Clone, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Coordinate {
     pub x: u16,
     pub y: u16,
@@ -17,6 +23,7 @@ impl From<TimedCoordinate> for Coordinate {
 }
 
 impl Coordinate {
+    /// Whether `other` is this cell or one of its 4-neighbours, i.e. within co-observation range.
     pub fn adj(&self, other: &Self) -> bool {
         self.manh_dist(other) <= 1
     }
@@ -34,6 +41,7 @@ impl Coordinate {
     }
 }
 
+/// A grid cell at a time step. Ordering compares only `t`.
 #[derive(Debug, Hash, Eq, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimedCoordinate {
     pub x: u16,
@@ -69,25 +77,32 @@ impl TimedCoordinate {
     }
 }
 
+/// A robot with its start and goal cells.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Agent {
     pub goal: Coordinate,
     pub name: String,
     pub start: Coordinate,
+    /// Its battery, unlimited if absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy: Option<Energy>,
 }
 
+/// A MAPF problem: the agents and the grid they move on.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct MapfInstance {
     pub agents: Vec<Agent>,
     pub map: Map,
 }
 
+/// Grid dimensions and blocked cells. Obstacles double as the attackers' targets.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Map {
     pub dimensions: Coordinate,
-    pub obstacles: HashSet<Coordinate>,
+    pub obstacles: BTreeSet<Coordinate>,
 }
 
+/// Solver statistics, `cost` is the sum of arrival times and `makespan` the latest one.
 #[allow(non_snake_case)] // inherit non_snake_case names from libMultiRobotPlanning
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Statistics {
@@ -97,13 +112,112 @@ pub struct Statistics {
     pub highLevelExpanded: u32,
     pub lowLevelExpanded: u32,
 }
+/// How a solution breaks its instance, see [`MapfSolution::validate`].
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum ViolationKind {
+    MissingSchedule,
+    UnknownAgent,
+    EmptySchedule,
+    WrongStart {
+        expected: Coordinate,
+        found: Coordinate,
+    },
+    WrongGoal {
+        expected: Coordinate,
+        found: Coordinate,
+    },
+    TimeMismatch {
+        found: usize,
+    },
+    OffMap {
+        at: Coordinate,
+    },
+    Obstacle {
+        at: Coordinate,
+    },
+    Dynamics {
+        from: Coordinate,
+        to: Coordinate,
+    },
+    VertexConflict {
+        other: String,
+        at: Coordinate,
+    },
+    EdgeConflict {
+        other: String,
+    },
+    Makespan {
+        stated: usize,
+        actual: usize,
+    },
+    /// The plan draws more than the agent's battery holds, exhausting it at this time step.
+    OverBudget {
+        needed: f64,
+        budget: f64,
+    },
+}
+
+/// A [`ViolationKind`] located at an agent and time step.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PlanViolation {
+    pub agent: String,
+    pub t: usize,
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for PlanViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at t={}: ", self.agent, self.t)?;
+        match &self.kind {
+            ViolationKind::MissingSchedule => write!(f, "no schedule in the solution"),
+            ViolationKind::UnknownAgent => write!(f, "scheduled but not in the instance"),
+            ViolationKind::EmptySchedule => write!(f, "empty schedule"),
+            ViolationKind::WrongStart { expected, found } => write!(
+                f,
+                "starts at ({}, {}) instead of ({}, {})",
+                found.x, found.y, expected.x, expected.y
+            ),
+            ViolationKind::WrongGoal { expected, found } => write!(
+                f,
+                "ends at ({}, {}) instead of ({}, {})",
+                found.x, found.y, expected.x, expected.y
+            ),
+            ViolationKind::TimeMismatch { found } => write!(f, "waypoint labelled t={}", found),
+            ViolationKind::OffMap { at } => write!(f, "off the map at ({}, {})", at.x, at.y),
+            ViolationKind::Obstacle { at } => write!(f, "inside obstacle ({}, {})", at.x, at.y),
+            ViolationKind::Dynamics { from, to } => write!(
+                f,
+                "jumps from ({}, {}) to ({}, {})",
+                from.x, from.y, to.x, to.y
+            ),
+            ViolationKind::VertexConflict { other, at } => {
+                write!(f, "collides with {} at ({}, {})", other, at.x, at.y)
+            }
+            ViolationKind::EdgeConflict { other } => write!(f, "swaps places with {}", other),
+            ViolationKind::Makespan { stated, actual } => write!(
+                f,
+                "longest schedule ends at t={} but the stated makespan is {}",
+                actual, stated
+            ),
+            ViolationKind::OverBudget { needed, budget } => write!(
+                f,
+                "battery of {} exhausted, the plan draws {}",
+                budget, needed
+            ),
+        }
+    }
+}
+
+/// A plan: one timed path per agent name. Analyses expect it padded with
+/// [`extend_stay_in_place`](crate::utils::extend_stay_in_place).
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct MapfSolution {
     pub statistics: Statistics,
-    pub schedule: HashMap<String, Vec<TimedCoordinate>>,
+    pub schedule: BTreeMap<String, Vec<TimedCoordinate>>,
 }
 
 impl MapfSolution {
+    /// Longest stretch of time steps in which no other agent is adjacent to `attacker_name`.
     pub fn max_inter_observation_time(&self, attacker_name: &String) -> usize {
         let mut iot = 1;
         let mut miot = 1;
@@ -126,6 +240,149 @@ impl MapfSolution {
         }
         miot
     }
+    /// Every violation of the instance. Collisions are checked with agents staying in place
+    /// after the end of their schedule.
+    pub fn validate(&self, instance: &MapfInstance) -> Vec<PlanViolation> {
+        let mut violations = Vec::new();
+        let violation = |agent: &String, t: usize, kind: ViolationKind| PlanViolation {
+            agent: agent.clone(),
+            t,
+            kind,
+        };
+        let mut names: Vec<&String> = self.schedule.keys().collect();
+        names.sort();
+        for name in names {
+            if !instance.agents.iter().any(|agent| agent.name == *name) {
+                violations.push(violation(name, 0, ViolationKind::UnknownAgent));
+            }
+        }
+        let mut paths: Vec<(&String, &Vec<TimedCoordinate>)> = Vec::new();
+        for agent in &instance.agents {
+            let path = match self.schedule.get(&agent.name) {
+                Some(path) if path.is_empty() => {
+                    violations.push(violation(&agent.name, 0, ViolationKind::EmptySchedule));
+                    continue;
+                }
+                Some(path) => path,
+                None => {
+                    violations.push(violation(&agent.name, 0, ViolationKind::MissingSchedule));
+                    continue;
+                }
+            };
+            let first: Coordinate = path[0].into();
+            if first != agent.start {
+                violations.push(violation(
+                    &agent.name,
+                    0,
+                    ViolationKind::WrongStart {
+                        expected: agent.start,
+                        found: first,
+                    },
+                ));
+            }
+            let last: Coordinate = (*path.last().unwrap()).into();
+            if last != agent.goal {
+                violations.push(violation(
+                    &agent.name,
+                    path.len() - 1,
+                    ViolationKind::WrongGoal {
+                        expected: agent.goal,
+                        found: last,
+                    },
+                ));
+            }
+            for (t, tc) in path.iter().enumerate() {
+                let coord: Coordinate = (*tc).into();
+                if tc.t != t {
+                    violations.push(violation(
+                        &agent.name,
+                        t,
+                        ViolationKind::TimeMismatch { found: tc.t },
+                    ));
+                }
+                if coord.x >= instance.map.dimensions.x || coord.y >= instance.map.dimensions.y {
+                    violations.push(violation(
+                        &agent.name,
+                        t,
+                        ViolationKind::OffMap { at: coord },
+                    ));
+                } else if instance.map.obstacles.contains(&coord) {
+                    violations.push(violation(
+                        &agent.name,
+                        t,
+                        ViolationKind::Obstacle { at: coord },
+                    ));
+                }
+                if t > 0 && path[t - 1].manh_dist(tc) > 1 {
+                    violations.push(violation(
+                        &agent.name,
+                        t,
+                        ViolationKind::Dynamics {
+                            from: path[t - 1].into(),
+                            to: coord,
+                        },
+                    ));
+                }
+            }
+            if let Some(energy) = &agent.energy {
+                if let Some((t, needed)) = energy.exhausted(path, self.statistics.makespan) {
+                    violations.push(violation(
+                        &agent.name,
+                        t,
+                        ViolationKind::OverBudget {
+                            needed,
+                            budget: energy.budget,
+                        },
+                    ));
+                }
+            }
+            paths.push((&agent.name, path));
+        }
+        let position = |path: &Vec<TimedCoordinate>, t: usize| -> Coordinate {
+            path[min(t, path.len() - 1)].into()
+        };
+        let end = paths.iter().map(|(_, path)| path.len()).max().unwrap_or(0);
+        for t in 0..end {
+            for (i, (a_name, a_path)) in paths.iter().enumerate() {
+                for (b_name, b_path) in paths.iter().skip(i + 1) {
+                    if position(a_path, t) == position(b_path, t) {
+                        violations.push(violation(
+                            a_name,
+                            t,
+                            ViolationKind::VertexConflict {
+                                other: (*b_name).clone(),
+                                at: position(a_path, t),
+                            },
+                        ));
+                    } else if t > 0
+                        && position(a_path, t - 1) == position(b_path, t)
+                        && position(b_path, t - 1) == position(a_path, t)
+                    {
+                        violations.push(violation(
+                            a_name,
+                            t,
+                            ViolationKind::EdgeConflict {
+                                other: (*b_name).clone(),
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+        if end > 0 && end - 1 != self.statistics.makespan {
+            let (longest, _) = paths.iter().find(|(_, path)| path.len() == end).unwrap();
+            violations.push(violation(
+                longest,
+                end - 1,
+                ViolationKind::Makespan {
+                    stated: self.statistics.makespan,
+                    actual: end - 1,
+                },
+            ));
+        }
+        violations
+    }
+    /// Collision, dynamics and map-bounds check of a padded (possibly truncated) schedule.
     pub fn valid(&self, instance: &MapfInstance) -> bool {
         let mut t: usize = 0;
         loop {
@@ -146,7 +403,7 @@ impl MapfSolution {
                 > 0
             {
                 return false;
-            } // off the map or obstacle collision
+            } // off the map
             if t == self.statistics.makespan {
                 break;
             }
@@ -169,9 +426,11 @@ impl MapfSolution {
     }
 }
 
+/// `schedule[name][t]` is the first time step of `name`'s plan that is *not* yet announced at
+/// time `t`.
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Announcements {
-    pub schedule: HashMap<String, Vec<usize>>,
+    pub schedule: BTreeMap<String, Vec<usize>>,
 }
 
 impl Announcements {
@@ -234,4 +493,154 @@ mod test {
             }
         }
     }
+
+    fn two_agent_instance() -> MapfInstance {
+        let mut obstacles = BTreeSet::new();
+        obstacles.insert(Coordinate { x: 1, y: 1 });
+        MapfInstance {
+            agents: vec![
+                Agent {
+                    name: "agent0".to_string(),
+                    start: Coordinate { x: 0, y: 0 },
+                    goal: Coordinate { x: 2, y: 0 },
+                    energy: None,
+                },
+                Agent {
+                    name: "agent1".to_string(),
+                    start: Coordinate { x: 0, y: 2 },
+                    goal: Coordinate { x: 2, y: 2 },
+                    energy: None,
+                },
+            ],
+            map: Map {
+                dimensions: Coordinate { x: 3, y: 3 },
+                obstacles,
+            },
+        }
+    }
+
+    fn straight_solution() -> MapfSolution {
+        let mut schedule = BTreeMap::new();
+        for (name, y) in &[("agent0", 0), ("agent1", 2)] {
+            schedule.insert(
+                name.to_string(),
+                (0..3)
+                    .map(|t| TimedCoordinate {
+                        x: t as u16,
+                        y: *y,
+                        t,
+                    })
+                    .collect(),
+            );
+        }
+        MapfSolution {
+            statistics: Statistics {
+                cost: 4,
+                makespan: 2,
+                runtime: 0.0,
+                highLevelExpanded: 0,
+                lowLevelExpanded: 0,
+            },
+            schedule,
+        }
+    }
+
+    #[test]
+    fn validate_accepts_valid_plan() {
+        assert_eq!(straight_solution().validate(&two_agent_instance()), vec![]);
+    }
+
+    #[test]
+    fn validate_reports_typed_violations() {
+        let instance = two_agent_instance();
+        let mut solution = straight_solution();
+        solution.schedule.get_mut("agent0").unwrap()[1] = TimedCoordinate { x: 1, y: 1, t: 1 };
+        solution.schedule.get_mut("agent1").unwrap()[2].t = 7;
+        solution.schedule.insert("ghost".to_string(), vec![]);
+        let kinds: Vec<(String, usize, ViolationKind)> = solution
+            .validate(&instance)
+            .into_iter()
+            .map(|v| (v.agent, v.t, v.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("ghost".to_string(), 0, ViolationKind::UnknownAgent),
+                (
+                    "agent0".to_string(),
+                    1,
+                    ViolationKind::Obstacle {
+                        at: Coordinate { x: 1, y: 1 }
+                    }
+                ),
+                (
+                    "agent0".to_string(),
+                    1,
+                    ViolationKind::Dynamics {
+                        from: Coordinate { x: 0, y: 0 },
+                        to: Coordinate { x: 1, y: 1 }
+                    }
+                ),
+                (
+                    "agent0".to_string(),
+                    2,
+                    ViolationKind::Dynamics {
+                        from: Coordinate { x: 1, y: 1 },
+                        to: Coordinate { x: 2, y: 0 }
+                    }
+                ),
+                (
+                    "agent1".to_string(),
+                    2,
+                    ViolationKind::TimeMismatch { found: 7 }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_reports_missing_schedule_and_conflicts() {
+        let mut instance = two_agent_instance();
+        instance.agents.push(Agent {
+            name: "agent2".to_string(),
+            start: Coordinate { x: 2, y: 1 },
+            goal: Coordinate { x: 2, y: 1 },
+            energy: None,
+        });
+        let mut solution = straight_solution();
+        // agent1 cuts through agent0's goal after agent0 has parked
+        solution.schedule.insert(
+            "agent1".to_string(),
+            vec![
+                TimedCoordinate { x: 0, y: 2, t: 0 },
+                TimedCoordinate { x: 0, y: 1, t: 1 },
+                TimedCoordinate { x: 0, y: 0, t: 2 },
+                TimedCoordinate { x: 1, y: 0, t: 3 },
+                TimedCoordinate { x: 2, y: 0, t: 4 },
+            ],
+        );
+        let kinds: Vec<ViolationKind> = solution
+            .validate(&instance)
+            .into_iter()
+            .map(|v| v.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ViolationKind::WrongGoal {
+                    expected: Coordinate { x: 2, y: 2 },
+                    found: Coordinate { x: 2, y: 0 }
+                },
+                ViolationKind::MissingSchedule,
+                ViolationKind::VertexConflict {
+                    other: "agent1".to_string(),
+                    at: Coordinate { x: 2, y: 0 }
+                },
+                ViolationKind::Makespan {
+                    stated: 2,
+                    actual: 4
+                },
+            ]
+        );
+    }
 }
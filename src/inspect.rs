@@ -0,0 +1,363 @@
+//! Interactive terminal viewer for the fixpoint loop of cautious attempts, see
+//! [`inspect_cautious`].
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::Print;
+use crossterm::terminal::{
+    self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
+use crossterm::{execute, queue};
+use matrix_display::{cell, matrix, style, Format, MatrixDisplay};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::error::{Error, Result};
+use crate::experiments::{
+    trace_cautious_attempt, CautiousAttemptResult, CautiousEvent, CautiousStep, CautiousTrace,
+    CautiousVerdict,
+};
+use crate::inputs::{Announcements, Coordinate, MapfInstance, MapfSolution};
+
+const LEGEND: &str = "A attacker  @ observable attacker  D defender  . observed  \
+                      ! dead end at t+s  X obstacle  $ target";
+const KEYS: &str = "left/right step  up/down previous/next t  r next restart  \
+                    tab/shift-tab attacker  home/end  q quit";
+
+/// Steps through the cautious attempts on `safe` in the terminal, starting with `attacker`.
+/// Shows, at every iteration of the fixpoint loop, the attacker's and defenders' floods, the
+/// cells the defenders observe and the conflicts found so far. `solution` should already be
+/// padded with [`extend_stay_in_place`](crate::extend_stay_in_place).
+pub fn inspect_cautious(
+    instance: &MapfInstance,
+    solution: &MapfSolution,
+    announcements: &Announcements,
+    attacker: &str,
+    safe: Coordinate,
+) -> Result<()> {
+    let mut viewer = Viewer {
+        instance,
+        solution,
+        announcements,
+        safe,
+        attacker: instance
+            .agents
+            .iter()
+            .position(|agent| agent.name == attacker)
+            .unwrap_or(0),
+        step: 0,
+        traces: HashMap::new(),
+    };
+    let mut out = io::stdout();
+    enable_raw_mode().map_err(terminal_error)?;
+    let _restore = Restore;
+    execute!(out, EnterAlternateScreen, Hide).map_err(terminal_error)?;
+    viewer.run(&mut out).map_err(terminal_error)
+}
+
+fn terminal_error(source: io::Error) -> Error {
+    Error::Io {
+        path: "terminal".to_string(),
+        source,
+    }
+}
+
+// leaves the alternate screen however the viewer exits
+struct Restore;
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+struct Viewer<'a> {
+    instance: &'a MapfInstance,
+    solution: &'a MapfSolution,
+    announcements: &'a Announcements,
+    safe: Coordinate,
+    // index into instance.agents
+    attacker: usize,
+    step: usize,
+    // traced lazily, an attempt can take a while
+    traces: HashMap<usize, (CautiousAttemptResult, CautiousTrace)>,
+}
+
+impl<'a> Viewer<'a> {
+    fn run(&mut self, out: &mut impl Write) -> io::Result<()> {
+        self.select(out, self.attacker, 0)?;
+        loop {
+            self.draw(out)?;
+            let code = match event::read()? {
+                Event::Key(KeyEvent {
+                    code,
+                    kind: KeyEventKind::Press,
+                    ..
+                }) => code,
+                _ => continue,
+            };
+            let steps = &self.traces[&self.attacker].1.steps;
+            let t = steps[self.step].t;
+            let agents = self.instance.agents.len();
+            match code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                    self.step = (self.step + 1).min(steps.len() - 1)
+                }
+                KeyCode::Left | KeyCode::Char('h') => self.step = self.step.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    if let Some(next) = steps.iter().position(|step| step.t > t) {
+                        self.step = next;
+                    }
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    // the start of this t, or of the previous one if already there
+                    let start = first_step_at(steps, t);
+                    self.step = match steps[..start].last() {
+                        Some(previous) if self.step == start => first_step_at(steps, previous.t),
+                        _ => start,
+                    };
+                }
+                KeyCode::Char('r') => {
+                    if let Some(next) = steps[self.step + 1..].iter().position(|step| {
+                        step.events
+                            .iter()
+                            .any(|event| matches!(event, CautiousEvent::Restart { .. }))
+                    }) {
+                        self.step += next + 1;
+                    }
+                }
+                KeyCode::Home => self.step = 0,
+                KeyCode::End => self.step = steps.len() - 1,
+                KeyCode::Tab | KeyCode::Char('n') => {
+                    self.select(out, (self.attacker + 1) % agents, t)?
+                }
+                KeyCode::BackTab | KeyCode::Char('p') => {
+                    self.select(out, (self.attacker + agents - 1) % agents, t)?
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // shows `attacker` at the first step of `t`, or its last step if its attempt ended earlier
+    fn select(&mut self, out: &mut impl Write, attacker: usize, t: usize) -> io::Result<()> {
+        if !self.traces.contains_key(&attacker) {
+            let name = &self.instance.agents[attacker].name;
+            execute!(
+                out,
+                Clear(ClearType::All),
+                MoveTo(0, 0),
+                Print(format!("tracing {} ...", name))
+            )?;
+            let traced = trace_cautious_attempt(
+                self.instance,
+                self.solution,
+                self.announcements,
+                name,
+                &self.safe,
+            );
+            self.traces.insert(attacker, traced);
+        }
+        self.attacker = attacker;
+        self.step = first_step_at(&self.traces[&attacker].1.steps, t);
+        Ok(())
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (res, trace) = &self.traces[&self.attacker];
+        let step = &trace.steps[self.step];
+        let attacker = &self.instance.agents[self.attacker].name;
+        let (columns, _) = terminal::size()?;
+        let wide = 2 * self.instance.map.dimensions.x < columns;
+        let mut lines = vec![
+            format!(
+                "{} ({}/{}) -> ({}, {}): {}",
+                attacker,
+                self.attacker + 1,
+                self.instance.agents.len(),
+                self.safe.x,
+                self.safe.y,
+                match res.verdict {
+                    CautiousVerdict::Secured => "secured",
+                    CautiousVerdict::NotSecured => "not secured",
+                    CautiousVerdict::Unknown => "unknown",
+                }
+            ),
+            format!(
+                "step {}/{}  t = {}, s = {}  |A| = {}  observable {}  conflicts {} ({} at t+s)",
+                self.step + 1,
+                trace.steps.len(),
+                step.t,
+                step.s,
+                step.floods[attacker].len(),
+                step.floods[attacker].intersection(&step.observed).count(),
+                step.conflicts.len(),
+                step.conflicts
+                    .iter()
+                    .filter(|tc| tc.t == step.t + step.s)
+                    .count()
+            ),
+        ];
+        lines.extend(step.events.iter().map(describe));
+        lines.push(String::new());
+        let mut board = Vec::new();
+        print_board(&mut board, self.instance, &self.safe, attacker, step, wide);
+        lines.extend(
+            String::from_utf8_lossy(&board)
+                // borderless cells still print NUL borders
+                .replace('\0', "")
+                .split("\r\n")
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string()),
+        );
+        lines.push(String::new());
+        lines.push(LEGEND.to_string());
+        lines.push(KEYS.to_string());
+        queue!(out, Clear(ClearType::All))?;
+        for (row, line) in lines.iter().enumerate() {
+            queue!(out, MoveTo(0, row as u16), Print(line))?;
+        }
+        out.flush()
+    }
+}
+
+fn first_step_at(steps: &[CautiousStep], t: usize) -> usize {
+    steps
+        .iter()
+        .position(|step| step.t >= t)
+        .unwrap_or(steps.len() - 1)
+}
+
+fn describe(event: &CautiousEvent) -> String {
+    match event {
+        CautiousEvent::Restart { agent } => {
+            format!("{}'s flood hit a dead end, restarting from s = 0", agent)
+        }
+        CautiousEvent::Fixpoint => "floods stopped changing: not secured".to_string(),
+        CautiousEvent::Observable { at, attack } => format!(
+            "attacker observable at ({}, {}): {}",
+            at.x,
+            at.y,
+            if *attack {
+                "an attack exists, not secured"
+            } else {
+                "no attack, next t"
+            }
+        ),
+    }
+}
+
+// writes the map as coloured cells, row 0 at the top
+fn print_board(
+    out: &mut impl Write,
+    instance: &MapfInstance,
+    safe: &Coordinate,
+    attacker_name: &str,
+    step: &CautiousStep,
+    wide: bool,
+) {
+    let width = instance.map.dimensions.x;
+    let index = |coord: &Coordinate| (coord.y * width + coord.x) as usize;
+    let mut board = vec![(' ', 250, 0); (width * instance.map.dimensions.y).into()];
+    let mut paint = |coord: &Coordinate, mark| {
+        if coord.x < width && coord.y < instance.map.dimensions.y {
+            board[index(coord)] = mark;
+        }
+    };
+    step.observed
+        .iter()
+        .for_each(|coord| paint(coord, ('.', 244, 0)));
+    for (name, flood) in &step.floods {
+        for coord in flood {
+            paint(
+                coord,
+                if name != attacker_name {
+                    ('D', 39, 0)
+                } else if step.observed.contains(coord) {
+                    ('@', 231, 160)
+                } else {
+                    ('A', 196, 0)
+                },
+            );
+        }
+    }
+    step.conflicts
+        .iter()
+        .filter(|tc| tc.t == step.t + step.s)
+        .for_each(|&tc| paint(&tc.into(), ('!', 201, 0)));
+    instance
+        .map
+        .obstacles
+        .iter()
+        .for_each(|coord| paint(coord, ('X', 250, 240)));
+    paint(safe, ('$', 0, 220));
+    let colored_board = board
+        .into_iter()
+        .map(|(mark, fg, bg)| cell::Cell::new(mark, fg, bg))
+        .collect::<Vec<_>>();
+    let format = Format::new(if wide { 2 } else { 1 }, 1);
+    let mut data = matrix::Matrix::new(width.into(), colored_board);
+    let display = MatrixDisplay::new(&format, &mut data);
+    display.print(out, &style::BordersStyle::None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::{Agent, Map, TimedCoordinate};
+    use std::collections::HashSet;
+
+    #[test]
+    fn board_marks_floods_observations_and_dead_ends() {
+        let at = |x, y| Coordinate { x, y };
+        let instance = MapfInstance {
+            agents: vec![
+                Agent {
+                    name: "attacker".to_string(),
+                    start: at(0, 0),
+                    goal: at(0, 0),
+                    energy: None,
+                },
+                Agent {
+                    name: "defender".to_string(),
+                    start: at(3, 1),
+                    goal: at(3, 1),
+                    energy: None,
+                },
+            ],
+            map: Map {
+                dimensions: at(4, 2),
+                obstacles: vec![at(1, 1), at(2, 1)].into_iter().collect(),
+            },
+        };
+        let mut floods = HashMap::new();
+        floods.insert("attacker".to_string(), vec![at(0, 0), at(2, 0)]);
+        floods.insert("defender".to_string(), vec![at(3, 1)]);
+        let step = CautiousStep {
+            t: 2,
+            s: 1,
+            floods: floods
+                .into_iter()
+                .map(|(name, flood)| (name, flood.into_iter().collect()))
+                .collect(),
+            observed: vec![at(2, 0), at(3, 0), at(3, 1)].into_iter().collect(),
+            conflicts: vec![
+                TimedCoordinate { x: 1, y: 0, t: 3 },
+                TimedCoordinate { x: 0, y: 1, t: 2 },
+            ]
+            .into_iter()
+            .collect::<HashSet<_>>(),
+            events: Vec::new(),
+        };
+        let mut board = Vec::new();
+        print_board(&mut board, &instance, &at(2, 1), "attacker", &step, false);
+        let marks: String = String::from_utf8_lossy(&board)
+            .chars()
+            .filter(|c| "A@D.!X$ \n".contains(*c))
+            .collect();
+        assert_eq!(marks, "A!@.\n X$D\n");
+    }
+}
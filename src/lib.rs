@@ -0,0 +1,84 @@
+//! Plan-deviation attack analysis for multi-robot systems with co-observations and
+//! horizon-limiting announcements.
+//!
+//! The crate reads MAPF instances and solutions in the libMultiRobotPlanning YAML format
+//! ([`MapfInstance`], [`MapfSolution`]), computes how far ahead each robot announces its plan
+//! ([`Announcements`]), and evaluates two attacker models against them: a bold attacker that
+//! simulates a concrete deviation ([`run_bold_attempts`]) and a cautious attacker that
+//! over-approximates every deviation the defenders could not tell apart from the plan
+//! ([`run_cautious_analysis`], [`run_cautious_attempt`]).
+//!
+//! ```no_run
+//! use announcenet::{
+//!     compute_kahead_announcements, extend_stay_in_place, run_cautious_attempt, CautiousBudget,
+//!     MapfInstance, MapfSolution,
+//! };
+//!
+//! let instance: MapfInstance = serde_yaml::from_str(&std::fs::read_to_string("map.yaml")?)?;
+//! let mut solution: MapfSolution =
+//!     serde_yaml::from_str(&std::fs::read_to_string("plan.yaml")?)?;
+//! extend_stay_in_place(&mut solution);
+//! let announcements =
+//!     compute_kahead_announcements(&instance.agents, 5, solution.statistics.makespan);
+//! let attacker = &instance.agents[0].name;
+//! for safe in &instance.map.obstacles {
+//!     let budget = CautiousBudget::default();
+//!     let res = run_cautious_attempt(&instance, &solution, &announcements, attacker, safe, &budget);
+//!     println!("{:?}: {:?}", safe, res.verdict);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod channel;
+pub mod checkpoint;
+pub mod commitment;
+pub mod compiled;
+pub mod energy;
+pub mod equivocation;
+pub mod error;
+pub mod experiments;
+pub mod inputs;
+pub mod inspect;
+pub mod lifelong;
+pub mod loader;
+pub mod planner;
+pub mod plotting;
+pub mod provenance;
+pub mod replay;
+pub mod response;
+pub mod sensing;
+pub mod suite;
+pub mod summary;
+pub mod utils;
+
+pub use crate::channel::Channel;
+pub use crate::checkpoint::{Attempt, Checkpoint};
+pub use crate::commitment::{commit, verify, CommitmentViolation, Transcript};
+pub use crate::compiled::{AgentId, Compiled};
+pub use crate::energy::Energy;
+pub use crate::equivocation::{EquivocationOutcome, PerRecipient};
+pub use crate::error::{Error, Result};
+pub use crate::experiments::{
+    resume_bold_attempts, resume_cautious_analysis, resume_equivocating_attempts,
+    run_bold_attempts, run_cautious_analysis, run_cautious_attempt, run_equivocating_attempts,
+    trace_bold_attempt, trace_cautious_attempt, BoldAttemptResult, BoldDefense, BoldEvent,
+    BoldExperimentResult, BoldStep, BoldTrace, CautiousAttemptResult, CautiousBudget,
+    CautiousEvent, CautiousExperimentResult, CautiousStep, CautiousTrace, CautiousVerdict,
+    SAMPLED_ATTACKERS, SAMPLED_TARGETS,
+};
+pub use crate::inputs::{
+    Agent, Announcements, Coordinate, Map, MapfInstance, MapfSolution, PlanViolation, Statistics,
+    TimedCoordinate, ViolationKind,
+};
+pub use crate::lifelong::{Segment, Stream};
+pub use crate::planner::{plan_cbs, plan_prioritized, repair_inter_observation, RepairReport};
+pub use crate::plotting::{generate_plots, PlotSpec};
+pub use crate::provenance::{FileDigest, Provenance};
+pub use crate::response::{Response, ResponseOutcome, ResponseStrategy};
+pub use crate::sensing::{Estimate, LiarBehavior, SensedDetections, SensingModel};
+pub use crate::suite::{run_suite, Manifest, SuiteIndex};
+pub use crate::summary::summarize;
+pub use crate::utils::{
+    compute_kahead_announcements, compute_kgrouped_announcements, compute_robust_announcements,
+    extend_stay_in_place,
+};
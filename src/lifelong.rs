@@ -0,0 +1,272 @@
+//! Plans that never reach a global makespan: a stream of plan segments, each replanning the
+//! agents that got new tasks, cut into finite windows the analyses can take, see [`Stream`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::energy::Energy;
+use crate::inputs::{
+    Agent, Coordinate, Map, MapfInstance, MapfSolution, Statistics, TimedCoordinate,
+};
+
+/// New paths for the agents replanned at time step `issued`, each starting there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub issued: usize,
+    pub schedule: BTreeMap<String, Vec<TimedCoordinate>>,
+}
+
+/// Segments in the order they were issued, read one per line as they come, see
+/// [`load_stream`](crate::loader::load_stream). Every agent waits at its start until a segment
+/// plans it, follows the path of the latest segment that replanned it, and waits at its last
+/// cell whenever that runs out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stream {
+    pub segments: Vec<Segment>,
+}
+
+impl Stream {
+    /// Why the stream does not fit `instance`, if it does not: segments issued out of order,
+    /// agents not in the instance, and paths that do not start when their segment was issued
+    /// or not where the agent was by then.
+    pub fn inconsistency(&self, instance: &MapfInstance) -> Option<String> {
+        self.stitch(instance, usize::MAX).err()
+    }
+
+    /// Every agent's plan from time step 0 on, as far as the segments go. The stream must fit
+    /// `instance`.
+    pub fn plans(&self, instance: &MapfInstance) -> BTreeMap<String, Vec<TimedCoordinate>> {
+        self.known(instance, usize::MAX)
+    }
+
+    // every agent's plan as far as the segments issued by `t` go
+    fn known(&self, instance: &MapfInstance, t: usize) -> BTreeMap<String, Vec<TimedCoordinate>> {
+        self.stitch(instance, t)
+            .expect("streams are checked against their instance when loaded")
+    }
+
+    /// The windows of `length` time steps starting every `stride` time steps from 0, as long as
+    /// the segments plan some agent up to their ends, and at least the first one. Each comes
+    /// with the time step it starts at, and is an instance from where the agents are at its
+    /// start to where they are at its end with its plan shifted to start at 0, ready for
+    /// [`extend_stay_in_place`](crate::extend_stay_in_place) and the analyses. A window holds
+    /// the plans as the segments issued by its start have them, so it never announces a
+    /// replanning before it is issued. Batteries hold what the plans left of them by the start
+    /// of the window.
+    pub fn windows(
+        &self,
+        instance: &MapfInstance,
+        length: usize,
+        stride: usize,
+    ) -> Vec<(usize, MapfInstance, MapfSolution)> {
+        let plans = self.plans(instance);
+        let end = plans.values().map(|plan| plan.len() - 1).max().unwrap_or(0);
+        (0..)
+            .step_by(stride)
+            .take_while(|&from| from == 0 || from + length <= end)
+            .map(|from| {
+                let (window, solution) =
+                    window(instance, &self.known(instance, from), from, length);
+                (from, window, solution)
+            })
+            .collect()
+    }
+
+    // the plans of the segments issued by `until`
+    fn stitch(
+        &self,
+        instance: &MapfInstance,
+        until: usize,
+    ) -> Result<BTreeMap<String, Vec<TimedCoordinate>>, String> {
+        let mut plans: BTreeMap<String, Vec<TimedCoordinate>> = instance
+            .agents
+            .iter()
+            .map(|agent| (agent.name.clone(), vec![agent.start.as_time(0)]))
+            .collect();
+        let mut issued = 0;
+        for (i, segment) in self.segments.iter().enumerate() {
+            if segment.issued > until {
+                break;
+            }
+            if segment.issued < issued {
+                return Err(format!(
+                    "segment {} is issued at t={}, before the one issued at t={}",
+                    i, segment.issued, issued
+                ));
+            }
+            issued = segment.issued;
+            for (name, path) in &segment.schedule {
+                let plan = plans.get_mut(name).ok_or_else(|| {
+                    format!("segment {} plans {}, which is not in the instance", i, name)
+                })?;
+                let first = match path.first() {
+                    Some(first) if first.t == issued => *first,
+                    _ => {
+                        return Err(format!(
+                            "segment {} does not start {}'s path at t={}",
+                            i, name, issued
+                        ))
+                    }
+                };
+                while plan.len() <= issued {
+                    let last = *plan.last().unwrap();
+                    plan.push(last.as_time(last.t + 1));
+                }
+                let was = at(plan, issued);
+                if Coordinate::from(first) != was {
+                    return Err(format!(
+                        "segment {} starts {} at ({}, {}), but it is at ({}, {}) by t={}",
+                        i, name, first.x, first.y, was.x, was.y, issued
+                    ));
+                }
+                plan.truncate(issued);
+                plan.extend_from_slice(path);
+            }
+        }
+        Ok(plans)
+    }
+}
+
+// where `plan` has its agent at `t`, waiting at its last cell once it runs out
+fn at(plan: &[TimedCoordinate], t: usize) -> Coordinate {
+    plan[t.min(plan.len() - 1)].into()
+}
+
+fn window(
+    instance: &MapfInstance,
+    plans: &BTreeMap<String, Vec<TimedCoordinate>>,
+    from: usize,
+    length: usize,
+) -> (MapfInstance, MapfSolution) {
+    let mut cost = 0;
+    let mut schedule = BTreeMap::new();
+    let agents = instance
+        .agents
+        .iter()
+        .map(|agent| {
+            let plan = &plans[&agent.name];
+            let path: Vec<TimedCoordinate> = (0..length + 1)
+                .map(|t| at(plan, from + t).as_time(t))
+                .collect();
+            let goal = at(plan, from + length);
+            // arrives once it stays at the goal for good
+            cost += (0..length + 1)
+                .rev()
+                .take_while(|&t| Coordinate::from(path[t]) == goal)
+                .last()
+                .unwrap_or(length) as u32;
+            let energy = agent.energy.map(|energy| Energy {
+                budget: energy.budget
+                    - (1..from + 1)
+                        .map(|t| energy.step(at(plan, t - 1), at(plan, t)))
+                        .sum::<f64>(),
+                ..energy
+            });
+            schedule.insert(agent.name.clone(), path);
+            Agent {
+                goal,
+                name: agent.name.clone(),
+                start: at(plan, from),
+                energy,
+            }
+        })
+        .collect();
+    let window = MapfInstance {
+        agents,
+        map: Map {
+            dimensions: instance.map.dimensions,
+            obstacles: instance.map.obstacles.clone(),
+        },
+    };
+    let solution = MapfSolution {
+        statistics: Statistics {
+            cost,
+            makespan: length,
+            runtime: 0.0,
+            highLevelExpanded: 0,
+            lowLevelExpanded: 0,
+        },
+        schedule,
+    };
+    (window, solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 6 by 2 grid, agent0 picks up a second task at the east end once it reaches its first
+    const INSTANCE: &str = "
+map: {dimensions: [6, 2], obstacles: []}
+agents:
+  - {name: agent0, start: [0, 0], goal: [2, 0]}
+  - {name: agent1, start: [0, 1], goal: [0, 1]}
+";
+
+    fn path(issued: usize, cells: &[(u16, u16)]) -> Vec<TimedCoordinate> {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(t, &(x, y))| TimedCoordinate {
+                x,
+                y,
+                t: issued + t,
+            })
+            .collect()
+    }
+
+    fn segment(issued: usize, name: &str, cells: &[(u16, u16)]) -> Segment {
+        Segment {
+            issued,
+            schedule: vec![(name.to_string(), path(issued, cells))]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn windows_follow_the_latest_segment_and_shift_to_time_step_zero() {
+        let instance: MapfInstance = serde_yaml::from_str(INSTANCE).unwrap();
+        let stream = Stream {
+            segments: vec![
+                segment(0, "agent0", &[(0, 0), (1, 0), (2, 0)]),
+                segment(4, "agent0", &[(2, 0), (3, 0), (4, 0), (5, 0)]),
+            ],
+        };
+        assert_eq!(stream.inconsistency(&instance), None);
+        assert_eq!(stream.plans(&instance)["agent0"].len(), 8);
+
+        let windows = stream.windows(&instance, 3, 2);
+        let starts: Vec<usize> = windows.iter().map(|(from, _, _)| *from).collect();
+        assert_eq!(starts, vec![0, 2, 4]);
+        // the second task is only issued at 4, after the window at 2 starts
+        let (_, window, solution) = &windows[1];
+        assert_eq!(solution.validate(window), vec![]);
+        assert_eq!(solution.schedule["agent0"], path(0, &[(2, 0); 4]));
+        assert_eq!(window.agents[0].start, Coordinate { x: 2, y: 0 });
+        assert_eq!(window.agents[0].goal, Coordinate { x: 2, y: 0 });
+        // never planned, agent1 waits at its start
+        assert_eq!(solution.schedule["agent1"], path(0, &[(0, 1); 4]));
+        assert_eq!(solution.statistics.cost, 0);
+        let (_, window, solution) = &windows[2];
+        assert_eq!(solution.validate(window), vec![]);
+        assert_eq!(
+            solution.schedule["agent0"],
+            path(0, &[(2, 0), (3, 0), (4, 0), (5, 0)])
+        );
+        assert_eq!(window.agents[0].goal, Coordinate { x: 5, y: 0 });
+        assert_eq!(solution.statistics.cost, 3);
+
+        // a replanned path has to pick up where the agent is
+        let jump = Stream {
+            segments: vec![
+                segment(0, "agent0", &[(0, 0), (1, 0), (2, 0)]),
+                segment(1, "agent0", &[(2, 0), (3, 0)]),
+            ],
+        };
+        assert_eq!(
+            jump.inconsistency(&instance),
+            Some("segment 1 starts agent0 at (2, 0), but it is at (1, 0) by t=1".to_string())
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Reading and writing the YAML inputs and outputs, with consistency checks between them.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+
+use crate::error::{Error, Result};
+use crate::inputs::{Announcements, MapfInstance, MapfSolution, ViolationKind};
+use crate::lifelong::{Segment, Stream};
+
+fn io_error(path: &str) -> impl FnOnce(std::io::Error) -> Error + '_ {
+    move |source| Error::Io {
+        path: path.to_string(),
+        source,
+    }
+}
+
+/// Reads and parses any YAML file.
+pub fn read_yaml<T: DeserializeOwned>(path: &str) -> Result<T> {
+    let yaml = fs::read_to_string(path).map_err(io_error(path))?;
+    serde_yaml::from_str(&yaml).map_err(|source| Error::Yaml {
+        path: path.to_string(),
+        source,
+    })
+}
+
+/// Serializes `value` to YAML at `path`, replacing any existing file.
+pub fn write_yaml<T: Serialize>(path: &str, value: &T) -> Result<()> {
+    let yaml = serde_yaml::to_string(value).map_err(|source| Error::Yaml {
+        path: path.to_string(),
+        source,
+    })?;
+    fs::write(path, yaml).map_err(io_error(path))
+}
+
+/// Creates an empty file at `path`, failing if it exists unless `overwrite` is set. Used to
+/// claim an output path before starting a long computation.
+pub fn touch(path: &str, overwrite: bool) -> Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(overwrite)
+        .create_new(!overwrite)
+        .open(path)
+        .map(|_| ())
+        .map_err(io_error(path))
+}
+
+pub fn load_instance(path: &str) -> Result<MapfInstance> {
+    read_yaml(path)
+}
+
+/// Loads a solution and rejects it unless it schedules exactly the instance's agents and
+/// passes [`MapfSolution::validate`].
+pub fn load_solution(
+    instance: &MapfInstance,
+    instance_path: &str,
+    path: &str,
+) -> Result<MapfSolution> {
+    let solution: MapfSolution = read_yaml(path)?;
+    let violations = solution.validate(instance);
+    let unmatched: Vec<&str> = violations
+        .iter()
+        .filter(|violation| {
+            violation.kind == ViolationKind::MissingSchedule
+                || violation.kind == ViolationKind::UnknownAgent
+        })
+        .map(|violation| violation.agent.as_str())
+        .collect();
+    if !unmatched.is_empty() {
+        return Err(Error::Inconsistent {
+            instance: instance_path.to_string(),
+            other: path.to_string(),
+            reason: format!("agents {} aren't in both", unmatched.join(", ")),
+        });
+    }
+    if !violations.is_empty() {
+        return Err(Error::Validation {
+            instance: instance_path.to_string(),
+            solution: path.to_string(),
+            violations,
+        });
+    }
+    Ok(solution)
+}
+
+/// Loads custom announcements and checks they cover every agent up to the makespan.
+pub fn load_announcements(
+    instance: &MapfInstance,
+    instance_path: &str,
+    makespan: usize,
+    path: &str,
+) -> Result<Announcements> {
+    let announcements: Announcements = read_yaml(path)?;
+    for agent in &instance.agents {
+        let reason = match announcements.schedule.get(&agent.name) {
+            None => format!("no announcements for {}", agent.name),
+            Some(sched) if sched.len() <= makespan => format!(
+                "announcements for {} end at t={}, the makespan is {}",
+                agent.name,
+                sched.len(),
+                makespan
+            ),
+            Some(sched) => match sched.iter().enumerate().find(|(t, &until)| until <= *t) {
+                Some((t, _)) => format!("{} announces nothing at t={}", agent.name, t),
+                None => continue,
+            },
+        };
+        return Err(Error::Inconsistent {
+            instance: instance_path.to_string(),
+            other: path.to_string(),
+            reason,
+        });
+    }
+    Ok(announcements)
+}
+
+/// Loads a stream of plan segments, one JSON object per line, and checks it fits the instance,
+/// see [`Stream::inconsistency`].
+pub fn load_stream(instance: &MapfInstance, instance_path: &str, path: &str) -> Result<Stream> {
+    let lines = fs::read_to_string(path).map_err(io_error(path))?;
+    let mut segments = Vec::new();
+    for (number, line) in lines.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let segment: Segment = serde_json::from_str(line).map_err(|source| Error::Json {
+            path: path.to_string(),
+            line: number + 1,
+            source,
+        })?;
+        segments.push(segment);
+    }
+    let stream = Stream { segments };
+    match stream.inconsistency(instance) {
+        Some(reason) => Err(Error::Inconsistent {
+            instance: instance_path.to_string(),
+            other: path.to_string(),
+            reason,
+        }),
+        None => Ok(stream),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const INSTANCE: &str = "
+map: {dimensions: [3, 1], obstacles: []}
+agents:
+  - {name: agent0, start: [0, 0], goal: [2, 0]}
+";
+
+    fn solution(schedule: &str) -> String {
+        format!(
+            "
+statistics: {{cost: 2, makespan: 2, runtime: 0.0, highLevelExpanded: 0, lowLevelExpanded: 0}}
+schedule:
+{}
+",
+            schedule
+        )
+    }
+
+    fn file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("loader-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn each_kind_of_bad_input_exits_with_its_own_code() {
+        let instance_path = file("instance.yaml", INSTANCE);
+        let instance_path = instance_path.to_str().unwrap();
+        let instance = load_instance(instance_path).unwrap();
+        let load = |name: &str, schedule: &str| {
+            let path = file(name, &solution(schedule));
+            let loaded = load_solution(&instance, instance_path, path.to_str().unwrap());
+            fs::remove_file(path).unwrap();
+            loaded
+        };
+
+        assert!(load(
+            "valid.yaml",
+            "  agent0: [{x: 0, y: 0, t: 0}, {x: 1, y: 0, t: 1}, {x: 2, y: 0, t: 2}]"
+        )
+        .is_ok());
+        let missing = std::env::temp_dir().join(format!("loader-{}-missing", std::process::id()));
+        assert_eq!(
+            code(load_solution(
+                &instance,
+                instance_path,
+                missing.to_str().unwrap()
+            )),
+            2
+        );
+        assert_eq!(code(load("yaml.yaml", "  agent0: [{x: 0")), 3);
+        // jumps two cells at once
+        assert_eq!(
+            code(load(
+                "validation.yaml",
+                "  agent0: [{x: 0, y: 0, t: 0}, {x: 2, y: 0, t: 1}]"
+            )),
+            4
+        );
+        assert_eq!(
+            code(load(
+                "inconsistent.yaml",
+                "  agent1: [{x: 0, y: 0, t: 0}, {x: 1, y: 0, t: 1}, {x: 2, y: 0, t: 2}]"
+            )),
+            5
+        );
+
+        // announcements have to cover every agent up to the makespan
+        let announcements = file("announcements.yaml", "schedule: {agent0: [1, 2]}");
+        let loaded =
+            load_announcements(&instance, instance_path, 2, announcements.to_str().unwrap());
+        fs::remove_file(announcements).unwrap();
+        assert_eq!(code(loaded), 5);
+
+        // a segment stream with a line cut short
+        let stream = file(
+            "stream.jsonl",
+            "{\"issued\": 0, \"schedule\": {}}\n{\"issued\": 1, \"sched\n",
+        );
+        let loaded = load_stream(&instance, instance_path, stream.to_str().unwrap());
+        fs::remove_file(stream).unwrap();
+        fs::remove_file(instance_path).unwrap();
+        let error = loaded.unwrap_err();
+        assert_eq!(error.exit_code(), 3);
+        assert!(matches!(error, Error::Json { line: 2, .. }));
+    }
+
+    fn code<T>(loaded: Result<T>) -> i32 {
+        loaded.err().map_or(0, |error| error.exit_code())
+    }
+}
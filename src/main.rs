@@ -1,178 +1,675 @@
-use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
+use clap::ArgMatches;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::process::exit;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 mod app_args;
-mod experiments;
-mod inputs;
-mod utils;
 
 use crate::app_args::parse_opts;
-use crate::experiments::{run_bold_attempts, run_cautious_analysis};
-use crate::inputs::{Announcements, MapfInstance, MapfSolution};
-use crate::utils::{
+use announcenet::error::{Error, Result};
+use announcenet::inspect::inspect_cautious;
+use announcenet::loader::{
+    load_announcements, load_instance, load_solution, load_stream, read_yaml, touch, write_yaml,
+};
+use announcenet::plotting::{generate_heatmap, generate_plots, Level, ResultKind};
+use announcenet::provenance::{FileDigest, Provenance};
+use announcenet::replay::{render_replay, Scene};
+use announcenet::suite::{run_suite, JobStatus};
+use announcenet::summary::summarize;
+use announcenet::{
     compute_kahead_announcements, compute_kgrouped_announcements, compute_robust_announcements,
-    extend_stay_in_place, generate_plots,
+    extend_stay_in_place, plan_cbs, plan_prioritized, repair_inter_observation,
+    resume_bold_attempts, resume_cautious_analysis, resume_equivocating_attempts,
+    trace_bold_attempt, Announcements, Attempt, BoldDefense, CautiousBudget, Channel, Checkpoint,
+    Coordinate, Estimate, LiarBehavior, MapfInstance, MapfSolution, RepairReport, Response,
+    ResponseStrategy, SensingModel, SAMPLED_ATTACKERS, SAMPLED_TARGETS,
 };
 
 fn main() {
+    if let Err(why) = run() {
+        eprintln!("error: {}", why);
+        exit(why.exit_code());
+    }
+}
+
+fn run() -> Result<()> {
     let opts = parse_opts();
     match opts.subcommand() {
         ("compute-secure-announcements", Some(_)) => {
             eprintln!("feature not implemented, exiting");
             exit(1);
         }
-        ("analyze-attackers", Some(sub_c)) => {
-            // read in instance file
+        ("plan", Some(sub_c)) => {
             let instance_path = sub_c.value_of("mapf-instance").unwrap();
-            let mut instance_file = match File::open(&instance_path) {
-                Err(why) => panic!("couldn't open {}: {}", instance_path, why),
-                Ok(file) => file,
-            };
-            let mut instance_yaml = String::new();
-            match instance_file.read_to_string(&mut instance_yaml) {
-                Err(why) => panic!("couldn't read {}: {}", instance_path, why),
-                _ => {}
-            };
-            let instance: MapfInstance = match serde_yaml::from_str(&instance_yaml) {
-                Err(why) => panic!("error parsing {}: {}", instance_path, why),
-                Ok(instance) => instance,
+            let instance = load_instance(instance_path)?;
+
+            let solution = match sub_c.value_of("planner").unwrap() {
+                "prioritized" => plan_prioritized(&instance),
+                "cbs" => plan_cbs(&instance),
+                _ => unreachable!(),
             };
+            let mut solution = solution.ok_or_else(|| Error::NoSolution {
+                instance: instance_path.to_string(),
+            })?;
+            println!(
+                "cost {} makespan {} in {:.3}s ({} high-level, {} low-level expansions)",
+                solution.statistics.cost,
+                solution.statistics.makespan,
+                solution.statistics.runtime,
+                solution.statistics.highLevelExpanded,
+                solution.statistics.lowLevelExpanded
+            );
+            if let Some(bound) = sub_c.value_of("max-inter-observation") {
+                let bound = parse_arg("max-inter-observation", bound)?;
+                let (repaired, report) = repair_inter_observation(&instance, &solution, bound);
+                print_repair_report(&report);
+                solution = repaired;
+            }
 
-            // read in solution path
+            write_yaml(sub_c.value_of("output").unwrap(), &solution)?;
+        }
+        ("check-plan", Some(sub_c)) => {
+            let instance_path = sub_c.value_of("mapf-instance").unwrap();
+            let instance = load_instance(instance_path)?;
             let solution_path = sub_c.value_of("mapf-solution").unwrap();
-            let mut solution_file = match File::open(&solution_path) {
-                Err(why) => panic!("couldn't open {}: {}", solution_path, why),
-                Ok(file) => file,
-            };
-            let mut solution_yaml = String::new();
-            match solution_file.read_to_string(&mut solution_yaml) {
-                Err(why) => panic!("couldn't read {}: {}", solution_path, why),
-                _ => {}
-            };
-            let solution: MapfSolution = match serde_yaml::from_str(&solution_yaml) {
-                Err(why) => panic!("error parsing {}: {}", solution_path, why),
-                Ok(mut solution) => {
-                    extend_stay_in_place(&mut solution);
-                    solution
-                }
-            };
+            let solution: MapfSolution = read_yaml(solution_path)?;
 
-            // compute or read in announcements path
-            let announcements: Announcements = match sub_c
-                .value_of("announcement-strategy")
-                .unwrap()
-            {
-                "kahead" => {
-                    let k = sub_c
-                        .value_of("lookahead")
-                        .unwrap()
-                        .parse::<usize>()
-                        .unwrap();
-                    if sub_c.is_present("skip-large") && (k > solution.statistics.makespan + 1) {
-                        panic!("provided lookahead larger than makespan, skipping.");
-                    }
-                    compute_kahead_announcements(&instance.agents, k, solution.statistics.makespan)
-                }
-                "kgrouped" => {
-                    let k = sub_c
-                        .value_of("lookahead")
-                        .unwrap()
-                        .parse::<usize>()
-                        .unwrap();
-                    if sub_c.is_present("skip-large") && (k > solution.statistics.makespan + 1) {
-                        panic!("provided lookahead larger than makespan, skipping.");
-                    }
-                    compute_kgrouped_announcements(
-                        &instance.agents,
-                        k,
-                        solution.statistics.makespan,
-                    )
+            let violations = solution.validate(&instance);
+            if !violations.is_empty() {
+                return Err(Error::Validation {
+                    instance: instance_path.to_string(),
+                    solution: solution_path.to_string(),
+                    violations,
+                });
+            }
+            println!("{}: valid", solution_path);
+        }
+        ("repair-plan", Some(sub_c)) => {
+            let instance_path = sub_c.value_of("mapf-instance").unwrap();
+            let instance = load_instance(instance_path)?;
+            let solution_path = sub_c.value_of("mapf-solution").unwrap();
+            let solution = load_solution(&instance, instance_path, solution_path)?;
+
+            let bound = required(sub_c, "max-inter-observation")?;
+            let (repaired, report) = repair_inter_observation(&instance, &solution, bound);
+            print_repair_report(&report);
+
+            write_yaml(sub_c.value_of("output").unwrap(), &repaired)?;
+        }
+        ("window-stream", Some(sub_c)) => {
+            let instance_path = sub_c.value_of("mapf-instance").unwrap();
+            let instance = load_instance(instance_path)?;
+            let segments_path = sub_c.value_of("segments").unwrap();
+            let stream = load_stream(&instance, instance_path, segments_path)?;
+            let length = required(sub_c, "window")?;
+            let stride = parsed(sub_c, "stride")?.unwrap_or(length);
+
+            let output = Path::new(sub_c.value_of("output").unwrap());
+            let stem = Path::new(instance_path)
+                .file_stem()
+                .map_or("instance".into(), |stem| stem.to_string_lossy());
+            let mut names = String::new();
+            for (from, window, solution) in stream.windows(&instance, length, stride) {
+                let violations = solution.validate(&window);
+                if !violations.is_empty() {
+                    return Err(Error::Validation {
+                        instance: instance_path.to_string(),
+                        solution: format!("{} from t={}", segments_path, from),
+                        violations,
+                    });
                 }
-                "robust" => compute_robust_announcements(&instance, &solution),
-                "custom" => {
-                    let announcement_path = sub_c.value_of("custom-announcements").unwrap();
-                    let mut announcement_file = match File::open(&announcement_path) {
-                        Err(why) => panic!("couldn't open {}: {}", announcement_path, why),
-                        Ok(file) => file,
-                    };
-                    let mut announcement_yaml = String::new();
-                    match announcement_file.read_to_string(&mut announcement_yaml) {
-                        Err(why) => panic!("couldn't read {}: {}", announcement_path, why),
-                        _ => {}
-                    };
-                    match serde_yaml::from_str(&announcement_yaml) {
-                        Err(why) => panic!("error parsing {}: {}", announcement_path, why),
-                        Ok(announcement) => announcement,
+                let name = format!("{}_w{}", stem, from);
+                let path = |dir: &str| {
+                    let dir = output.join(dir);
+                    fs::create_dir_all(&dir).map_err(|source| Error::Io {
+                        path: dir.display().to_string(),
+                        source,
+                    })?;
+                    Ok(dir.join(format!("{}.yaml", name)).display().to_string())
+                };
+                write_yaml(&path("maps")?, &window)?;
+                write_yaml(&path("plans")?, &solution)?;
+                names.push_str(&name);
+                names.push('\n');
+            }
+            let instances = output.join("instances.txt").display().to_string();
+            fs::write(&instances, &names).map_err(|source| Error::Io {
+                path: instances.clone(),
+                source,
+            })?;
+            println!(
+                "{} windows of {} time steps, listed in {}",
+                names.lines().count(),
+                length,
+                instances
+            );
+        }
+        ("analyze-attackers", Some(sub_c)) => {
+            let instance_path = sub_c.value_of("mapf-instance").unwrap();
+            let instance = load_instance(instance_path)?;
+            let solution_path = sub_c.value_of("mapf-solution").unwrap();
+            let mut solution = load_solution(&instance, instance_path, solution_path)?;
+            extend_stay_in_place(&mut solution);
+
+            if sub_c.is_present("skip-large") {
+                if let Some(k) = parsed::<usize>(sub_c, "lookahead")? {
+                    if k > solution.statistics.makespan + 1 {
+                        eprintln!("provided lookahead larger than makespan, skipping.");
+                        return Ok(());
                     }
                 }
-                _ => unreachable!(),
-            };
+            }
+            let announcements = select_announcements(sub_c, &instance, instance_path, &solution)?;
 
             let output_path = sub_c.value_of("output").unwrap();
-            {
-                match OpenOptions::new()
-                    .write(true)
-                    .create_new(true)
-                    .open(output_path)
-                {
-                    Err(why) => panic!("couldn't touch {} for writing: {}", output_path, why),
-                    _ => {}
-                };
+            let resume = sub_c.is_present("resume");
+            if resume && fs::metadata(output_path).is_ok_and(|meta| meta.len() > 0) {
+                eprintln!("{} is already complete, nothing to resume.", output_path);
+                return Ok(());
             }
+            // a killed run leaves its output empty, resuming claims it again
+            touch(output_path, resume)?;
+            let checkpoint_path = format!("{}.partial.jsonl", output_path);
+            let mut provenance = record_provenance(sub_c, &instance, instance_path, solution_path)?;
+            let started = Instant::now();
 
             // run trials
             match sub_c.value_of("type").unwrap() {
                 "bold" => {
-                    let res = run_bold_attempts(
-                        instance,
-                        solution,
-                        announcements,
-                        !sub_c.is_present("no-mitigation"),
-                    );
+                    let checkpoint = open_checkpoint(&checkpoint_path, resume, &provenance)?;
+                    let mut res = match provenance.cross_checks {
+                        Some(cross_checks) => resume_equivocating_attempts(
+                            instance,
+                            solution,
+                            announcements,
+                            provenance.channel.as_ref(),
+                            cross_checks,
+                            checkpoint,
+                        )?,
+                        None => resume_bold_attempts(
+                            instance,
+                            solution,
+                            announcements,
+                            provenance.channel.as_ref(),
+                            &BoldDefense {
+                                mitigation: !sub_c.is_present("no-mitigation"),
+                                sensing: provenance.sensing.as_ref(),
+                                response: provenance.response.as_ref(),
+                            },
+                            checkpoint,
+                        )?,
+                    };
+                    if !sub_c.is_present("no-wall-time") {
+                        provenance.wall_time = Some(started.elapsed().as_secs_f64());
+                    }
+                    res.provenance = Some(provenance);
                     println!(
                         "{:>5} / {:>5} dangerous and undetected. {:.2} miss rate",
                         res.dangerous_undetected_count(),
                         res.attempts.len(),
                         res.miss_rate().unwrap_or(f64::NAN)
                     );
-                    let output_yaml = serde_yaml::to_string(&res).ok().unwrap();
-                    let mut output_file = match File::create(&output_path) {
-                        Err(why) => panic!("couldn't open {}: {}", output_path, why),
-                        Ok(file) => file,
-                    };
-                    match output_file.write_all(output_yaml.as_bytes()) {
-                        Err(why) => panic!("error writing to {}: {}", output_path, why),
-                        _ => {}
-                    };
+                    if let Some(rate) = res.caught_before_target_rate() {
+                        println!(
+                            "{:.2} caught by cross-checks before reaching the target",
+                            rate
+                        );
+                    }
+                    if let Some(rate) = res.stop_rate() {
+                        println!(
+                            "{:.2} of dangerous attempts stopped by the response before reaching the target",
+                            rate
+                        );
+                    }
+                    if let Some(sensing) = res.provenance.as_ref().and_then(|p| p.sensing) {
+                        print_estimate(
+                            "sensed miss rate",
+                            res.sensed_miss_rate(sensing.confidence),
+                        );
+                        print_estimate(
+                            "sensed false alarm rate",
+                            res.sensed_false_alarm_rate(sensing.confidence),
+                        );
+                    }
+                    write_yaml(output_path, &res)?;
                 }
                 "cautious" => {
-                    let res = run_cautious_analysis(instance, solution, announcements);
+                    let mut res = resume_cautious_analysis(
+                        instance,
+                        solution,
+                        announcements,
+                        provenance.channel.as_ref(),
+                        &cautious_budget(sub_c)?,
+                        open_checkpoint(&checkpoint_path, resume, &provenance)?,
+                    )?;
+                    if !sub_c.is_present("no-wall-time") {
+                        provenance.wall_time = Some(started.elapsed().as_secs_f64());
+                    }
+                    res.provenance = Some(provenance);
                     println!(
-                        "{:>5} / {:>5} secure.",
+                        "{:>5} / {:>5} secure, {} unknown.",
                         res.secure_count(),
                         res.attempts.len(),
+                        res.unknown_count(),
                     );
-                    let output_yaml = serde_yaml::to_string(&res).ok().unwrap();
-                    let mut output_file = match File::create(&output_path) {
-                        Err(why) => panic!("couldn't open {}: {}", output_path, why),
-                        Ok(file) => file,
-                    };
-                    match output_file.write_all(output_yaml.as_bytes()) {
-                        Err(why) => panic!("error writing to {}: {}", output_path, why),
-                        _ => {}
-                    };
+                    write_yaml(output_path, &res)?;
                 }
                 _ => unreachable!(),
             }
+            fs::remove_file(&checkpoint_path).map_err(|source| Error::Io {
+                path: checkpoint_path,
+                source,
+            })?;
         }
         ("generate-plots", Some(sub_c)) => {
             generate_plots(
                 sub_c.value_of("plot").unwrap(),
                 sub_c.value_of("output").unwrap(),
                 sub_c.is_present("force"),
+            )?;
+        }
+        ("generate-heatmap", Some(sub_c)) => {
+            let instance_path = sub_c.value_of("mapf-instance").unwrap();
+            let instance = load_instance(instance_path)?;
+            let solution = match sub_c.value_of("mapf-solution") {
+                Some(path) => Some(load_solution(&instance, instance_path, path)?),
+                None => None,
+            };
+            let (results, default_metric) = match sub_c.value_of("type").unwrap() {
+                "bold" => (ResultKind::Bold, "dangerous"),
+                "cautious" => (ResultKind::Cautious, "secured"),
+                _ => unreachable!(),
+            };
+            generate_heatmap(
+                &instance,
+                solution.as_ref(),
+                results,
+                sub_c.value_of("metric").unwrap_or(default_metric),
+                sub_c.value_of("output").unwrap(),
+                sub_c.is_present("force"),
+            )?;
+        }
+        ("run-suite", Some(sub_c)) => {
+            let exe = env::current_exe().map_err(|source| Error::Io {
+                path: "announcenet".to_string(),
+                source,
+            })?;
+            let index = run_suite(sub_c.value_of("manifest").unwrap(), &exe)?;
+            for status in &[
+                JobStatus::Done,
+                JobStatus::Existing,
+                JobStatus::Skipped,
+                JobStatus::Failed,
+                JobStatus::Timeout,
+            ] {
+                let count = index
+                    .jobs
+                    .iter()
+                    .filter(|report| report.status == *status)
+                    .count();
+                println!("{:>9}: {}", format!("{:?}", status).to_lowercase(), count);
+            }
+        }
+        ("summarize", Some(sub_c)) => {
+            let results = match sub_c.value_of("type").unwrap() {
+                "bold" => ResultKind::Bold,
+                "cautious" => ResultKind::Cautious,
+                _ => unreachable!(),
+            };
+            let level = match sub_c.value_of("level").unwrap() {
+                "experiment" => Level::Experiment,
+                "attempt" => Level::Attempt,
+                _ => unreachable!(),
+            };
+            summarize(
+                results,
+                level,
+                sub_c.value_of("output").unwrap(),
+                sub_c.is_present("force"),
+            )?;
+        }
+        ("replay", Some(sub_c)) => {
+            let instance_path = sub_c.value_of("mapf-instance").unwrap();
+            let instance = load_instance(instance_path)?;
+            let solution_path = sub_c.value_of("mapf-solution").unwrap();
+            let mut solution = load_solution(&instance, instance_path, solution_path)?;
+            extend_stay_in_place(&mut solution);
+            let announcements = select_announcements(sub_c, &instance, instance_path, &solution)?;
+            let attacker = sub_c.value_of("attacker").unwrap();
+            let safe = select_attack(sub_c, &instance, instance_path, attacker)?;
+            let (res, trace) = trace_bold_attempt(
+                &instance,
+                &solution,
+                &announcements,
+                attacker,
+                &safe,
+                !sub_c.is_present("no-mitigation"),
+            );
+            println!(
+                "{} -> {},{}: dangerous {}, detected {}",
+                attacker, safe.x, safe.y, res.dangerous, res.detected
             );
+            render_replay(
+                &Scene {
+                    instance: &instance,
+                    solution: &solution,
+                    announcements: &announcements,
+                    attacker,
+                    safe,
+                    trace: &trace,
+                },
+                sub_c.value_of("output").unwrap(),
+                required(sub_c, "delay")?,
+                sub_c.is_present("force"),
+            )?;
+        }
+        ("inspect-cautious", Some(sub_c)) => {
+            let instance_path = sub_c.value_of("mapf-instance").unwrap();
+            let instance = load_instance(instance_path)?;
+            let solution_path = sub_c.value_of("mapf-solution").unwrap();
+            let mut solution = load_solution(&instance, instance_path, solution_path)?;
+            extend_stay_in_place(&mut solution);
+            let announcements = select_announcements(sub_c, &instance, instance_path, &solution)?;
+            let attacker = sub_c
+                .value_of("attacker")
+                .unwrap_or(&instance.agents[0].name);
+            let safe = select_attack(sub_c, &instance, instance_path, attacker)?;
+            inspect_cautious(&instance, &solution, &announcements, attacker, safe)?;
         }
         _ => println!("{}", opts.usage()),
     };
+    Ok(())
+}
+
+// the --target obstacle, checking it and the attacker exist
+fn select_attack(
+    sub_c: &ArgMatches,
+    instance: &MapfInstance,
+    instance_path: &str,
+    attacker: &str,
+) -> Result<Coordinate> {
+    let target = sub_c.value_of("target").unwrap();
+    let mut parts = target
+        .split(',')
+        .map(|part| parse_arg("target", part.trim()));
+    let mut next = || {
+        parts.next().unwrap_or_else(|| {
+            Err(Error::Argument {
+                name: "target".to_string(),
+                value: target.to_string(),
+                reason: "expected <x>,<y>".to_string(),
+            })
+        })
+    };
+    let safe = Coordinate {
+        x: next()?,
+        y: next()?,
+    };
+    let reason = if !instance.agents.iter().any(|agent| agent.name == attacker) {
+        format!("no agent named {}", attacker)
+    } else if !instance.map.obstacles.contains(&safe) {
+        format!("target {},{} is not an obstacle", safe.x, safe.y)
+    } else {
+        return Ok(safe);
+    };
+    Err(Error::Inconsistent {
+        instance: instance_path.to_string(),
+        other: "--attacker and --target".to_string(),
+        reason,
+    })
+}
+
+// the attempts an earlier run of analyze-attackers with the same configuration finished, or
+// none when starting afresh
+fn open_checkpoint<T: Attempt + Serialize + DeserializeOwned>(
+    path: &str,
+    resume: bool,
+    provenance: &Provenance,
+) -> Result<Checkpoint<T>> {
+    if !resume {
+        return Checkpoint::create(path, provenance);
+    }
+    let checkpoint = Checkpoint::resume(path, provenance)?;
+    eprintln!(
+        "resuming from {} with {} attempts done.",
+        path,
+        checkpoint.len()
+    );
+    Ok(checkpoint)
+}
+
+// the per-attempt limits given by --attempt-timeout and --attempt-iterations
+fn cautious_budget(sub_c: &ArgMatches) -> Result<CautiousBudget> {
+    Ok(CautiousBudget {
+        wall_time: parsed(sub_c, "attempt-timeout")?.map(Duration::from_secs),
+        iterations: parsed(sub_c, "attempt-iterations")?,
+    })
+}
+
+// the sensing model given by --detection-probability, --false-positive-rate, --liars,
+// --liar-behavior and --quorum, if any
+fn sensing_model(sub_c: &ArgMatches) -> Result<Option<SensingModel>> {
+    let given = [
+        "detection-probability",
+        "false-positive-rate",
+        "liars",
+        "liar-behavior",
+        "quorum",
+    ];
+    if !given.iter().any(|name| sub_c.is_present(name)) {
+        return Ok(None);
+    }
+    Ok(Some(SensingModel {
+        detection_probability: parsed(sub_c, "detection-probability")?.unwrap_or(1.0),
+        false_positive_rate: parsed(sub_c, "false-positive-rate")?.unwrap_or(0.0),
+        liars: parsed(sub_c, "liars")?.unwrap_or(0),
+        liar_behavior: match sub_c.value_of("liar-behavior") {
+            Some("fabricate") => LiarBehavior::Fabricate,
+            Some("vouch") => LiarBehavior::Vouch,
+            _ => LiarBehavior::Omit,
+        },
+        quorum: parsed(sub_c, "quorum")?.unwrap_or(1),
+        runs: required(sub_c, "sensing-runs")?,
+        seed: required(sub_c, "seed")?,
+        confidence: required(sub_c, "confidence")?,
+    }))
+}
+
+fn channel(sub_c: &ArgMatches) -> Result<Option<Channel>> {
+    if !["drop-probability", "latency", "range"]
+        .iter()
+        .any(|name| sub_c.is_present(name))
+    {
+        return Ok(None);
+    }
+    Ok(Some(Channel {
+        drop_probability: parsed(sub_c, "drop-probability")?.unwrap_or(0.0),
+        latency: parsed(sub_c, "latency")?.unwrap_or(0),
+        range: parsed(sub_c, "range")?,
+        seed: required(sub_c, "seed")?,
+    }))
+}
+
+fn response(
+    sub_c: &ArgMatches,
+    instance: &MapfInstance,
+    instance_path: &str,
+) -> Result<Option<Response>> {
+    if !sub_c.is_present("responders") && !sub_c.is_present("security-robot") {
+        return Ok(None);
+    }
+    let security_robot = sub_c.value_of("security-robot").map(str::to_string);
+    if let Some(name) = &security_robot {
+        if !instance.agents.iter().any(|agent| agent.name == *name) {
+            return Err(Error::Inconsistent {
+                instance: instance_path.to_string(),
+                other: "--security-robot".to_string(),
+                reason: format!("no agent named {}", name),
+            });
+        }
+    }
+    Ok(Some(Response {
+        responders: parsed(sub_c, "responders")?.unwrap_or(1),
+        security_robot,
+        strategy: match sub_c.value_of("response") {
+            Some("cordon") => ResponseStrategy::Cordon,
+            _ => ResponseStrategy::Intercept,
+        },
+    }))
+}
+
+fn print_estimate(name: &str, estimate: Option<Estimate>) {
+    match estimate {
+        Some(estimate) => println!(
+            "{} {:.4}, {:.4} to {:.4} over {} runs",
+            name, estimate.rate, estimate.interval.0, estimate.interval.1, estimate.runs
+        ),
+        None => println!("{} undefined, no runs to estimate it from", name),
+    }
+}
+
+// inputs and options of analyze-attackers, the wall time is filled in once done
+fn record_provenance(
+    sub_c: &ArgMatches,
+    instance: &MapfInstance,
+    instance_path: &str,
+    solution_path: &str,
+) -> Result<Provenance> {
+    let attacker = sub_c.value_of("type").unwrap();
+    Ok(Provenance {
+        instance: FileDigest::of(instance_path)?,
+        solution: FileDigest::of(solution_path)?,
+        strategy: sub_c.value_of("announcement-strategy").unwrap().to_string(),
+        lookahead: match sub_c.value_of("announcement-strategy").unwrap() {
+            "kahead" | "kgrouped" => parsed(sub_c, "lookahead")?,
+            _ => None,
+        },
+        custom_announcements: sub_c
+            .value_of("custom-announcements")
+            .map(FileDigest::of)
+            .transpose()?,
+        attacker: attacker.to_string(),
+        mitigation: if attacker == "bold" {
+            Some(!sub_c.is_present("no-mitigation"))
+        } else {
+            None
+        },
+        attempt_timeout: match attacker {
+            "cautious" => parsed(sub_c, "attempt-timeout")?,
+            _ => None,
+        },
+        attempt_iterations: match attacker {
+            "cautious" => parsed(sub_c, "attempt-iterations")?,
+            _ => None,
+        },
+        channel: channel(sub_c)?,
+        sensing: match attacker {
+            "bold" => sensing_model(sub_c)?,
+            _ => None,
+        },
+        cross_checks: if attacker == "bold" && sub_c.is_present("equivocate") {
+            Some(!sub_c.is_present("no-cross-checks"))
+        } else {
+            None
+        },
+        response: match attacker {
+            "bold" => response(sub_c, instance, instance_path)?,
+            _ => None,
+        },
+        attackers_sampled: instance.agents.len().min(SAMPLED_ATTACKERS),
+        targets_sampled: instance.map.obstacles.len().min(SAMPLED_TARGETS),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        wall_time: None,
+    })
+}
+
+// compute or read in announcements for the strategy given by -a, -k and -c
+fn select_announcements(
+    sub_c: &ArgMatches,
+    instance: &MapfInstance,
+    instance_path: &str,
+    solution: &MapfSolution,
+) -> Result<Announcements> {
+    let makespan = solution.statistics.makespan;
+    let lookahead = || required(sub_c, "lookahead");
+    Ok(match sub_c.value_of("announcement-strategy").unwrap() {
+        "kahead" => compute_kahead_announcements(&instance.agents, lookahead()?, makespan),
+        "kgrouped" => compute_kgrouped_announcements(&instance.agents, lookahead()?, makespan),
+        "robust" => compute_robust_announcements(instance, solution),
+        "custom" => load_announcements(
+            instance,
+            instance_path,
+            makespan,
+            sub_c.value_of("custom-announcements").unwrap(),
+        )?,
+        _ => unreachable!(),
+    })
+}
+
+// `value` of the argument `name` parsed as a `T`
+fn parse_arg<T>(name: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|why: T::Err| Error::Argument {
+        name: name.to_string(),
+        value: value.to_string(),
+        reason: why.to_string(),
+    })
+}
+
+// the argument `name` parsed as a `T`, if given
+fn parsed<T>(sub_c: &ArgMatches, name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    sub_c
+        .value_of(name)
+        .map(|value| parse_arg(name, value))
+        .transpose()
+}
+
+// the argument `name`, which clap requires or defaults, parsed as a `T`
+fn required<T>(sub_c: &ArgMatches, name: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    parsed(sub_c, name).map(|value| value.expect("required or defaulted by app_args"))
+}
+
+fn print_repair_report(report: &RepairReport) {
+    let increase = report.cost_after as i64 - report.cost_before as i64;
+    // relative to nothing for an empty plan
+    let increase = if report.cost_before == 0 {
+        format!("{:+}", increase)
+    } else {
+        format!(
+            "{:+.1}%",
+            100.0 * increase as f64 / report.cost_before as f64
+        )
+    };
+    println!(
+        "cost {} -> {} ({}), makespan {} -> {}",
+        report.cost_before,
+        report.cost_after,
+        increase,
+        report.makespan_before,
+        report.makespan_after
+    );
+    println!(
+        "max inter-observation time {} -> {}, agents over bound {} -> {}",
+        report.max_inter_observation_time_before,
+        report.max_inter_observation_time_after,
+        report.over_bound_before,
+        report.over_bound_after
+    );
 }
@@ -0,0 +1,717 @@
+//! Built-in MAPF solvers producing [`MapfSolution`]s.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+use crate::inputs::{Agent, Coordinate, MapfInstance, MapfSolution, Statistics, TimedCoordinate};
+use crate::utils::extend_stay_in_place;
+
+#[derive(Debug, Clone, Default)]
+struct Constraints {
+    vertex: HashSet<TimedCoordinate>,
+    // (from, to): may not move from `from.t` to `to.t = from.t + 1` along this edge
+    edge: HashSet<(TimedCoordinate, TimedCoordinate)>,
+    // cells occupied forever from the given time on (agents parked at their goal)
+    parked: HashMap<Coordinate, usize>,
+}
+
+impl Constraints {
+    fn allows(&self, from: TimedCoordinate, to: TimedCoordinate) -> bool {
+        !self.vertex.contains(&to)
+            && !self.edge.contains(&(from, to))
+            && match self.parked.get(&to.into()) {
+                Some(&since) => to.t < since,
+                None => true,
+            }
+    }
+    fn last_constrained(&self) -> usize {
+        self.vertex
+            .iter()
+            .map(|tc| tc.t)
+            .chain(self.edge.iter().map(|(_, to)| to.t))
+            .chain(self.parked.values().cloned())
+            .max()
+            .unwrap_or(0)
+    }
+    fn can_finish(&self, goal: TimedCoordinate) -> bool {
+        // the agent stays at its goal forever after arriving
+        !self.parked.contains_key(&goal.into())
+            && self
+                .vertex
+                .iter()
+                .all(|tc| Coordinate::from(*tc) != Coordinate::from(goal) || tc.t < goal.t)
+    }
+    fn reserve(&mut self, path: &[Coordinate]) {
+        for (t, coord) in path.iter().enumerate() {
+            self.vertex.insert(coord.as_time(t));
+            if t > 0 {
+                // forbid swapping places with the reserved agent
+                self.edge
+                    .insert((coord.as_time(t - 1), path[t - 1].as_time(t)));
+            }
+        }
+        self.parked.insert(*path.last().unwrap(), path.len() - 1);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct OpenEntry {
+    f: usize,
+    g: usize,
+    node: TimedCoordinate,
+    gap: usize,
+}
+
+impl Ord for OpenEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // min-heap on f, breaking ties towards deeper nodes
+        other
+            .f
+            .cmp(&self.f)
+            .then(self.g.cmp(&other.g))
+            .then(other.node.t.cmp(&self.node.t))
+            .then((other.node.x, other.node.y).cmp(&(self.node.x, self.node.y)))
+            .then(other.gap.cmp(&self.gap))
+    }
+}
+
+impl PartialOrd for OpenEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn neighbors(instance: &MapfInstance, coord: Coordinate) -> Vec<Coordinate> {
+    let mut res = vec![coord];
+    if coord.x > 0 {
+        res.push(Coordinate {
+            x: coord.x - 1,
+            y: coord.y,
+        });
+    }
+    if coord.y > 0 {
+        res.push(Coordinate {
+            x: coord.x,
+            y: coord.y - 1,
+        });
+    }
+    if coord.x + 1 < instance.map.dimensions.x {
+        res.push(Coordinate {
+            x: coord.x + 1,
+            y: coord.y,
+        });
+    }
+    if coord.y + 1 < instance.map.dimensions.y {
+        res.push(Coordinate {
+            x: coord.x,
+            y: coord.y + 1,
+        });
+    }
+    res.retain(|c| !instance.map.obstacles.contains(c));
+    res
+}
+
+// true shortest-path distances to `goal` ignoring other agents, used as the A* heuristic
+fn distances_to(instance: &MapfInstance, goal: Coordinate) -> HashMap<Coordinate, usize> {
+    let mut dist: HashMap<Coordinate, usize> = HashMap::new();
+    let mut queue = VecDeque::new();
+    dist.insert(goal, 0);
+    queue.push_back(goal);
+    while let Some(coord) = queue.pop_front() {
+        let d = dist[&coord];
+        for n in neighbors(instance, coord) {
+            if let Entry::Vacant(entry) = dist.entry(n) {
+                entry.insert(d + 1);
+                queue.push_back(n);
+            }
+        }
+    }
+    dist
+}
+
+// fixed paths of the other agents and the longest tolerated gap between co-observations
+struct ObservationBound<'a> {
+    observers: &'a [Vec<Coordinate>],
+    bound: usize,
+}
+
+impl ObservationBound<'_> {
+    // mirrors the counting in `MapfSolution::max_inter_observation_time`
+    fn next_gap(&self, gap: usize, coord: Coordinate, t: usize) -> usize {
+        if self
+            .observers
+            .iter()
+            .any(|path| position(path, t).adj(&coord))
+        {
+            1
+        } else {
+            gap + 1
+        }
+    }
+    fn holds_while_parked(&self, goal: TimedCoordinate, mut gap: usize) -> bool {
+        let end = self
+            .observers
+            .iter()
+            .map(|path| path.len() - 1)
+            .max()
+            .unwrap_or(0);
+        for t in (goal.t + 1)..(end + 1) {
+            gap = self.next_gap(gap, goal.into(), t);
+            if gap > self.bound {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn space_time_astar(
+    instance: &MapfInstance,
+    agent: &Agent,
+    heuristic: &HashMap<Coordinate, usize>,
+    constraints: &Constraints,
+    observation: Option<&ObservationBound>,
+    expanded: &mut u32,
+) -> Option<Vec<Coordinate>> {
+    let h_start = *heuristic.get(&agent.start)?;
+    let horizon = constraints.last_constrained()
+        + (instance.map.dimensions.x as usize) * (instance.map.dimensions.y as usize);
+    let start = agent.start.as_time(0);
+    let start_gap = match observation {
+        Some(obs) => obs.next_gap(1, agent.start, 0),
+        None => 0,
+    };
+    if observation.is_some_and(|obs| start_gap > obs.bound) {
+        return None;
+    }
+    let mut open = BinaryHeap::new();
+    // search states are (node, time since last co-observation), the latter is 0 if unbounded
+    let mut parent: HashMap<(TimedCoordinate, usize), (TimedCoordinate, usize)> = HashMap::new();
+    let mut closed: HashSet<(TimedCoordinate, usize)> = HashSet::new();
+    open.push(OpenEntry {
+        f: h_start,
+        g: 0,
+        node: start,
+        gap: start_gap,
+    });
+    while let Some(OpenEntry { g, node, gap, .. }) = open.pop() {
+        if !closed.insert((node, gap)) {
+            continue;
+        }
+        *expanded += 1;
+        if Coordinate::from(node) == agent.goal
+            && constraints.can_finish(node)
+            && observation.is_none_or(|obs| obs.holds_while_parked(node, gap))
+        {
+            let mut path = vec![node.into()];
+            let mut curr = (node, gap);
+            while let Some(&prev) = parent.get(&curr) {
+                path.push(prev.0.into());
+                curr = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if node.t >= horizon {
+            continue;
+        }
+        for n in neighbors(instance, node.into()) {
+            let next = n.as_time(node.t + 1);
+            let next_gap = match observation {
+                Some(obs) => obs.next_gap(gap, n, next.t),
+                None => 0,
+            };
+            if observation.is_some_and(|obs| next_gap > obs.bound)
+                || closed.contains(&(next, next_gap))
+                || !constraints.allows(node, next)
+            {
+                continue;
+            }
+            let h = match heuristic.get(&n) {
+                Some(&h) => h,
+                None => continue,
+            };
+            if parent.contains_key(&(next, next_gap)) {
+                // already queued, and g is the time layer so it can't improve
+                continue;
+            }
+            parent.insert((next, next_gap), (node, gap));
+            open.push(OpenEntry {
+                f: g + 1 + h,
+                g: g + 1,
+                node: next,
+                gap: next_gap,
+            });
+        }
+    }
+    None
+}
+
+fn to_solution(
+    instance: &MapfInstance,
+    paths: &[Vec<Coordinate>],
+    runtime: f64,
+    high_level_expanded: u32,
+    low_level_expanded: u32,
+) -> MapfSolution {
+    MapfSolution {
+        statistics: Statistics {
+            cost: paths.iter().map(|path| (path.len() - 1) as u32).sum(),
+            makespan: paths.iter().map(|path| path.len() - 1).max().unwrap_or(0),
+            runtime,
+            highLevelExpanded: high_level_expanded,
+            lowLevelExpanded: low_level_expanded,
+        },
+        schedule: instance
+            .agents
+            .iter()
+            .zip(paths.iter())
+            .map(|(agent, path)| {
+                (
+                    agent.name.clone(),
+                    path.iter()
+                        .enumerate()
+                        .map(|(t, coord)| coord.as_time(t))
+                        .collect(),
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Plans agents one by one in instance order, bumping an agent that can't be planned to the
+/// front and restarting. Incomplete, but fast on large instances.
+pub fn plan_prioritized(instance: &MapfInstance) -> Option<MapfSolution> {
+    let start_time = Instant::now();
+    let mut low_level_expanded = 0;
+    let mut high_level_expanded = 0;
+    let heuristics: Vec<HashMap<Coordinate, usize>> = instance
+        .agents
+        .iter()
+        .map(|agent| distances_to(instance, agent.goal))
+        .collect();
+    let mut order: Vec<usize> = (0..instance.agents.len()).collect();
+    // on failure the stuck agent is bumped to the highest priority and planning restarts
+    'restart: while high_level_expanded <= instance.agents.len() {
+        high_level_expanded += 1;
+        let mut reservations = Constraints::default();
+        let mut paths: Vec<Vec<Coordinate>> = vec![Vec::new(); instance.agents.len()];
+        for (rank, &agent_idx) in order.iter().enumerate() {
+            match space_time_astar(
+                instance,
+                &instance.agents[agent_idx],
+                &heuristics[agent_idx],
+                &reservations,
+                None,
+                &mut low_level_expanded,
+            ) {
+                Some(path) => {
+                    reservations.reserve(&path);
+                    paths[agent_idx] = path;
+                }
+                None if rank > 0 => {
+                    order.remove(rank);
+                    order.insert(0, agent_idx);
+                    continue 'restart;
+                }
+                None => return None,
+            }
+        }
+        return Some(to_solution(
+            instance,
+            &paths,
+            start_time.elapsed().as_secs_f64(),
+            high_level_expanded as u32,
+            low_level_expanded,
+        ));
+    }
+    None
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Conflict {
+    Vertex(usize, usize, TimedCoordinate),
+    // agents, and the first agent's move (the second agent moves the opposite way)
+    Edge(usize, usize, TimedCoordinate, TimedCoordinate),
+}
+
+fn position(path: &[Coordinate], t: usize) -> Coordinate {
+    path[t.min(path.len() - 1)]
+}
+
+fn first_conflict(paths: &[Vec<Coordinate>]) -> Option<Conflict> {
+    let makespan = paths.iter().map(|path| path.len()).max().unwrap_or(0);
+    for t in 0..makespan {
+        for a in 0..paths.len() {
+            for b in (a + 1)..paths.len() {
+                if position(&paths[a], t) == position(&paths[b], t) {
+                    return Some(Conflict::Vertex(a, b, position(&paths[a], t).as_time(t)));
+                }
+                if t > 0
+                    && position(&paths[a], t - 1) == position(&paths[b], t)
+                    && position(&paths[b], t - 1) == position(&paths[a], t)
+                    && position(&paths[a], t - 1) != position(&paths[a], t)
+                {
+                    return Some(Conflict::Edge(
+                        a,
+                        b,
+                        position(&paths[a], t - 1).as_time(t - 1),
+                        position(&paths[a], t).as_time(t),
+                    ));
+                }
+            }
+        }
+    }
+    None
+}
+
+struct CbsNode {
+    cost: usize,
+    id: usize,
+    constraints: Vec<Constraints>,
+    paths: Vec<Vec<Coordinate>>,
+}
+
+impl PartialEq for CbsNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost && self.id == other.id
+    }
+}
+
+impl Eq for CbsNode {}
+
+impl Ord for CbsNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // min-heap on cost, FIFO among equal costs
+        other.cost.cmp(&self.cost).then(other.id.cmp(&self.id))
+    }
+}
+
+impl PartialOrd for CbsNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Conflict-based search, optimal in sum of costs.
+pub fn plan_cbs(instance: &MapfInstance) -> Option<MapfSolution> {
+    let start_time = Instant::now();
+    let mut low_level_expanded = 0;
+    let mut high_level_expanded = 0;
+    let heuristics: Vec<HashMap<Coordinate, usize>> = instance
+        .agents
+        .iter()
+        .map(|agent| distances_to(instance, agent.goal))
+        .collect();
+    let mut root = CbsNode {
+        cost: 0,
+        id: 0,
+        constraints: vec![Constraints::default(); instance.agents.len()],
+        paths: Vec::new(),
+    };
+    for (agent, heuristic) in instance.agents.iter().zip(heuristics.iter()) {
+        root.paths.push(space_time_astar(
+            instance,
+            agent,
+            heuristic,
+            &Constraints::default(),
+            None,
+            &mut low_level_expanded,
+        )?);
+    }
+    root.cost = root.paths.iter().map(|path| path.len() - 1).sum();
+    let mut next_id = 1;
+    let mut open = BinaryHeap::new();
+    open.push(root);
+    while let Some(node) = open.pop() {
+        high_level_expanded += 1;
+        let conflict = match first_conflict(&node.paths) {
+            Some(conflict) => conflict,
+            None => {
+                return Some(to_solution(
+                    instance,
+                    &node.paths,
+                    start_time.elapsed().as_secs_f64(),
+                    high_level_expanded,
+                    low_level_expanded,
+                ))
+            }
+        };
+        let branches = match conflict {
+            Conflict::Vertex(a, b, at) => vec![(a, Some(at), None), (b, Some(at), None)],
+            Conflict::Edge(a, b, from, to) => vec![
+                (a, None, Some((from, to))),
+                (
+                    b,
+                    None,
+                    Some((
+                        Coordinate::from(to).as_time(from.t),
+                        Coordinate::from(from).as_time(to.t),
+                    )),
+                ),
+            ],
+        };
+        for (agent_idx, vertex, edge) in branches {
+            let mut constraints = node.constraints.clone();
+            if let Some(at) = vertex {
+                constraints[agent_idx].vertex.insert(at);
+            }
+            if let Some(e) = edge {
+                constraints[agent_idx].edge.insert(e);
+            }
+            let path = match space_time_astar(
+                instance,
+                &instance.agents[agent_idx],
+                &heuristics[agent_idx],
+                &constraints[agent_idx],
+                None,
+                &mut low_level_expanded,
+            ) {
+                Some(path) => path,
+                None => continue,
+            };
+            let mut paths = node.paths.clone();
+            paths[agent_idx] = path;
+            open.push(CbsNode {
+                cost: paths.iter().map(|path| path.len() - 1).sum(),
+                id: next_id,
+                constraints,
+                paths,
+            });
+            next_id += 1;
+        }
+    }
+    None
+}
+
+/// Cost and inter-observation time before and after [`repair_inter_observation`].
+pub struct RepairReport {
+    pub cost_before: u32,
+    pub cost_after: u32,
+    pub makespan_before: usize,
+    pub makespan_after: usize,
+    pub max_inter_observation_time_before: usize,
+    pub max_inter_observation_time_after: usize,
+    pub over_bound_before: usize,
+    pub over_bound_after: usize,
+}
+
+fn inter_observation_times(instance: &MapfInstance, paths: &[Vec<Coordinate>]) -> Vec<usize> {
+    let mut solution = to_solution(instance, paths, 0.0, 0, 0);
+    extend_stay_in_place(&mut solution);
+    instance
+        .agents
+        .iter()
+        .map(|agent| solution.max_inter_observation_time(&agent.name))
+        .collect()
+}
+
+/// Replans agents one at a time against the fixed paths of all others so that they are
+/// co-observed at least every `bound` steps, keeping a replan only if fewer agents (or the
+/// worst agent by less) exceed the bound. The statistics describe the repair pass.
+pub fn repair_inter_observation(
+    instance: &MapfInstance,
+    solution: &MapfSolution,
+    bound: usize,
+) -> (MapfSolution, RepairReport) {
+    let start_time = Instant::now();
+    let mut low_level_expanded = 0;
+    let mut high_level_expanded = 0;
+    let heuristics: Vec<HashMap<Coordinate, usize>> = instance
+        .agents
+        .iter()
+        .map(|agent| distances_to(instance, agent.goal))
+        .collect();
+    let mut paths: Vec<Vec<Coordinate>> = instance
+        .agents
+        .iter()
+        .map(|agent| {
+            solution.schedule[&agent.name]
+                .iter()
+                .map(|&tc| tc.into())
+                .collect()
+        })
+        .collect();
+    let score = |iots: &[usize]| {
+        (
+            iots.iter().filter(|&&iot| iot > bound).count(),
+            iots.iter().cloned().max().unwrap_or(0),
+        )
+    };
+    let before = inter_observation_times(instance, &paths);
+    let mut iots = before.clone();
+    loop {
+        let mut improved = false;
+        let mut candidates: Vec<usize> = (0..paths.len()).filter(|&i| iots[i] > bound).collect();
+        candidates.sort_by_key(|&i| Reverse(iots[i]));
+        for agent_idx in candidates {
+            if iots[agent_idx] <= bound {
+                continue;
+            }
+            high_level_expanded += 1;
+            let others: Vec<Vec<Coordinate>> = paths
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != agent_idx)
+                .map(|(_, path)| path.clone())
+                .collect();
+            let mut reservations = Constraints::default();
+            for path in &others {
+                reservations.reserve(path);
+            }
+            let path = match space_time_astar(
+                instance,
+                &instance.agents[agent_idx],
+                &heuristics[agent_idx],
+                &reservations,
+                Some(&ObservationBound {
+                    observers: &others,
+                    bound,
+                }),
+                &mut low_level_expanded,
+            ) {
+                Some(path) => path,
+                None => continue,
+            };
+            let mut candidate = paths.clone();
+            candidate[agent_idx] = path;
+            let candidate_iots = inter_observation_times(instance, &candidate);
+            if score(&candidate_iots) < score(&iots) {
+                paths = candidate;
+                iots = candidate_iots;
+                improved = true;
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    let repaired = to_solution(
+        instance,
+        &paths,
+        start_time.elapsed().as_secs_f64(),
+        high_level_expanded,
+        low_level_expanded,
+    );
+    let report = RepairReport {
+        cost_before: solution.statistics.cost,
+        cost_after: repaired.statistics.cost,
+        makespan_before: solution.statistics.makespan,
+        makespan_after: repaired.statistics.makespan,
+        max_inter_observation_time_before: score(&before).1,
+        max_inter_observation_time_after: score(&iots).1,
+        over_bound_before: score(&before).0,
+        over_bound_after: score(&iots).0,
+    };
+    (repaired, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::Map;
+    use std::collections::BTreeSet;
+
+    fn corridor_swap() -> MapfInstance {
+        // two agents swap ends of a corridor with a single passing bay at (2, 1)
+        let mut obstacles = BTreeSet::new();
+        for x in 0..5 {
+            if x != 2 {
+                obstacles.insert(Coordinate { x, y: 1 });
+            }
+        }
+        MapfInstance {
+            agents: vec![
+                Agent {
+                    name: "agent0".to_string(),
+                    start: Coordinate { x: 0, y: 0 },
+                    goal: Coordinate { x: 4, y: 0 },
+                    energy: None,
+                },
+                Agent {
+                    name: "agent1".to_string(),
+                    start: Coordinate { x: 4, y: 0 },
+                    goal: Coordinate { x: 0, y: 0 },
+                    energy: None,
+                },
+            ],
+            map: Map {
+                dimensions: Coordinate { x: 5, y: 2 },
+                obstacles,
+            },
+        }
+    }
+
+    #[test]
+    fn cbs_solves_corridor_swap() {
+        let instance = corridor_swap();
+        let mut solution = plan_cbs(&instance).unwrap();
+        assert_eq!(solution.statistics.cost, 11);
+        assert_eq!(solution.statistics.makespan, 6);
+        assert_eq!(
+            solution.schedule["agent0"]
+                .first()
+                .map(|tc| Coordinate::from(*tc)),
+            Some(instance.agents[0].start)
+        );
+        extend_stay_in_place(&mut solution);
+        assert!(solution.valid(&instance));
+    }
+
+    #[test]
+    fn prioritized_solution_is_valid() {
+        // four agents crossing an open grid
+        let instance = MapfInstance {
+            agents: (0..4)
+                .map(|i| Agent {
+                    name: format!("agent{}", i),
+                    start: Coordinate { x: i, y: 0 },
+                    goal: Coordinate { x: 3 - i, y: 3 },
+                    energy: None,
+                })
+                .collect(),
+            map: Map {
+                dimensions: Coordinate { x: 4, y: 4 },
+                obstacles: BTreeSet::new(),
+            },
+        };
+        let mut solution = plan_prioritized(&instance).unwrap();
+        assert!(solution.statistics.cost >= plan_cbs(&instance).unwrap().statistics.cost);
+        extend_stay_in_place(&mut solution);
+        assert!(solution.valid(&instance));
+    }
+
+    #[test]
+    fn repair_adds_rendezvous() {
+        // agent1 drives past the stationary agent0 two rows away, never meeting it
+        let instance = MapfInstance {
+            agents: vec![
+                Agent {
+                    name: "agent0".to_string(),
+                    start: Coordinate { x: 2, y: 0 },
+                    goal: Coordinate { x: 2, y: 0 },
+                    energy: None,
+                },
+                Agent {
+                    name: "agent1".to_string(),
+                    start: Coordinate { x: 0, y: 2 },
+                    goal: Coordinate { x: 4, y: 2 },
+                    energy: None,
+                },
+            ],
+            map: Map {
+                dimensions: Coordinate { x: 5, y: 3 },
+                obstacles: BTreeSet::new(),
+            },
+        };
+        let solution = plan_cbs(&instance).unwrap();
+        let (mut repaired, report) = repair_inter_observation(&instance, &solution, 4);
+        assert_eq!(report.over_bound_before, 2);
+        assert_eq!(report.over_bound_after, 0);
+        assert!(report.cost_after > report.cost_before);
+        extend_stay_in_place(&mut repaired);
+        assert!(repaired.valid(&instance));
+    }
+}
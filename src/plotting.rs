@@ -0,0 +1,1686 @@
+//! Spec-driven plots over bold and cautious experiment results.
+//!
+//! A [`PlotSpec`] names the x metric, the y metrics, how to group, filter and aggregate the
+//! records read from result files. The figures from the paper are built-in specs, see
+//! [`PlotSpec::builtin`]; the YAML sources live in `plots/` and double as examples.
+//! [`generate_heatmap`] instead draws the map itself, coloured by a per-target-cell metric.
+
+use decorum::N64;
+use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
+use plotters::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{self, BufRead};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::experiments::{
+    BoldAttemptResult, BoldExperimentResult, CautiousAttemptResult, CautiousExperimentResult,
+    CautiousVerdict,
+};
+use crate::inputs::{Coordinate, MapfInstance, MapfSolution};
+use crate::loader::{read_yaml, touch};
+use crate::provenance::Provenance;
+use crate::sensing::Estimate;
+
+/// Fields of one experiment or attempt, by name. A missing field is undefined for that record,
+/// e.g. `miss` for an attempt that never reached its target.
+pub type Record = BTreeMap<String, Value>;
+
+/// One field of a [`Record`]: a metric, or a label such as the announcement strategy. Numbers
+/// order before labels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+}
+
+impl Value {
+    pub fn number(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            Value::Text(_) => None,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            (Value::Number(_), Value::Text(_)) => Ordering::Less,
+            (Value::Text(_), Value::Number(_)) => Ordering::Greater,
+            (Value::Text(a), Value::Text(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Built-in plots, by name, with their YAML specs.
+pub const BUILTIN_PLOTS: &[(&str, &str)] = &[
+    (
+        "succ-vs-kahead",
+        include_str!("../plots/succ-vs-kahead.yaml"),
+    ),
+    (
+        "succ-vs-max-inter-obs",
+        include_str!("../plots/succ-vs-max-inter-obs.yaml"),
+    ),
+    (
+        "succ-vs-kgrouped",
+        include_str!("../plots/succ-vs-kgrouped.yaml"),
+    ),
+    (
+        "succ-vs-robust",
+        include_str!("../plots/succ-vs-robust.yaml"),
+    ),
+    (
+        "succ-vs-kahead-ci",
+        include_str!("../plots/succ-vs-kahead-ci.yaml"),
+    ),
+    (
+        "succ-vs-kahead-box",
+        include_str!("../plots/succ-vs-kahead-box.yaml"),
+    ),
+    (
+        "secure-vs-kahead",
+        include_str!("../plots/secure-vs-kahead.yaml"),
+    ),
+    (
+        "secure-vs-max-inter-obs",
+        include_str!("../plots/secure-vs-max-inter-obs.yaml"),
+    ),
+    (
+        "secure-vs-kgrouped",
+        include_str!("../plots/secure-vs-kgrouped.yaml"),
+    ),
+    (
+        "secure-vs-robust",
+        include_str!("../plots/secure-vs-robust.yaml"),
+    ),
+    (
+        "secure-vs-kahead-ci",
+        include_str!("../plots/secure-vs-kahead-ci.yaml"),
+    ),
+];
+
+/// Which attacker wrote the result files.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultKind {
+    Bold,
+    Cautious,
+}
+
+/// Whether each result file contributes one record, or one record per attempt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    #[default]
+    Experiment,
+    Attempt,
+}
+
+/// How the y values sharing an x value (and group) are reduced to one point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregate {
+    #[default]
+    Mean,
+    Median,
+    Sum,
+    Count,
+    Min,
+    Max,
+    /// Plot every record as its own point.
+    None,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Style {
+    #[default]
+    Line,
+    Points,
+    /// Quartiles and 1.5 IQR whiskers of the values at each x.
+    Box,
+    /// Kernel density of the values at each x.
+    Violin,
+}
+
+/// Spread drawn around each aggregated point, as a band for lines and as error bars for points.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interval {
+    /// One standard error either side of the mean.
+    Stderr,
+    /// Percentile bootstrap interval of the aggregate, at the spec's `confidence`.
+    Bootstrap,
+}
+
+/// The x axis: which field it shows, and optionally its label, range and bin width.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XAxis {
+    pub metric: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<[f64; 2]>,
+    /// Rounds x values down to multiples of this before aggregating.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct YAxis {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<[f64; 2]>,
+}
+
+/// One y metric to draw, labelled in the legend with `label` or else the metric name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Series {
+    pub metric: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Keeps records whose `field` is defined and satisfies every bound given. `eq` also matches
+/// labels, e.g. `strategy`, the other bounds only numbers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Filter {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eq: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub le: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ge: Option<f64>,
+}
+
+impl Filter {
+    pub fn accepts(&self, record: &Record) -> bool {
+        let value = match record.get(&self.field) {
+            Some(value) => value,
+            None => return false,
+        };
+        if self.eq.as_ref().is_some_and(|bound| value != bound) {
+            return false;
+        }
+        let value = match value.number() {
+            Some(value) => value,
+            None => {
+                return self.lt.is_none()
+                    && self.le.is_none()
+                    && self.gt.is_none()
+                    && self.ge.is_none()
+            }
+        };
+        self.lt.is_none_or(|bound| value < bound)
+            && self.le.is_none_or(|bound| value <= bound)
+            && self.gt.is_none_or(|bound| value > bound)
+            && self.ge.is_none_or(|bound| value >= bound)
+    }
+}
+
+/// A complete plot description, see the module docs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlotSpec {
+    pub title: String,
+    pub results: ResultKind,
+    #[serde(default)]
+    pub level: Level,
+    pub x: XAxis,
+    #[serde(default)]
+    pub y: YAxis,
+    pub series: Vec<Series>,
+    /// Draws each series once per distinct combination of these fields.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<Filter>,
+    #[serde(default)]
+    pub aggregate: Aggregate,
+    #[serde(default)]
+    pub style: Style,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<Interval>,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default = "default_resamples")]
+    pub resamples: usize,
+    /// Seeds the bootstrap so reruns draw the same intervals.
+    #[serde(default)]
+    pub seed: u64,
+    /// Writes the number of records behind each point next to it.
+    #[serde(default)]
+    pub counts: bool,
+}
+
+fn default_confidence() -> f64 {
+    0.95
+}
+
+fn default_resamples() -> usize {
+    1000
+}
+
+/// One aggregated value, or a single record when the spec doesn't aggregate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+    /// Number of records behind this point.
+    pub n: usize,
+    pub interval: Option<(f64, f64)>,
+    /// The y values behind this point, for box and violin plots.
+    pub samples: Vec<f64>,
+}
+
+/// One drawn line or point cloud.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub label: String,
+    pub points: Vec<Point>,
+}
+
+impl PlotSpec {
+    /// The built-in spec called `name`, if there is one.
+    pub fn builtin(name: &str) -> Option<PlotSpec> {
+        BUILTIN_PLOTS
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, yaml)| serde_yaml::from_str(yaml).expect("built-in plot specs are valid"))
+    }
+
+    /// Resolves a built-in name, or else reads `plot` as a path to a YAML spec.
+    pub fn load(plot: &str) -> Result<PlotSpec> {
+        let spec = match PlotSpec::builtin(plot) {
+            Some(spec) => spec,
+            None if Path::new(plot).is_file() => read_yaml(plot)?,
+            None => {
+                return Err(Error::Plot(format!(
+                    "{} is neither a built-in plot nor a spec file",
+                    plot
+                )))
+            }
+        };
+        spec.check()
+            .map_err(|why| Error::Plot(format!("{}: {}", plot, why)))?;
+        Ok(spec)
+    }
+
+    /// Rejects option combinations that have no meaningful rendering.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        match (self.interval, self.aggregate) {
+            (Some(_), Aggregate::None) | (Some(_), Aggregate::Count) => {
+                return Err("intervals need an aggregate other than none or count".to_string())
+            }
+            (Some(Interval::Stderr), aggregate) if aggregate != Aggregate::Mean => {
+                return Err("stderr intervals need the mean aggregate".to_string())
+            }
+            _ => (),
+        }
+        if !(self.confidence > 0.0 && self.confidence < 1.0) {
+            return Err("confidence must be between 0 and 1".to_string());
+        }
+        if self.interval == Some(Interval::Bootstrap) && self.resamples == 0 {
+            return Err("bootstrap needs at least one resample".to_string());
+        }
+        Ok(())
+    }
+
+    /// Flattens one result file into records at this spec's level.
+    pub fn read_records(&self, path: &str) -> Result<Vec<Record>> {
+        read_records(self.results, self.level, path)
+    }
+
+    /// Fails on the first result file none of whose records has a field the filters need,
+    /// rather than leaving it out of the plot without a word, e.g. a `strategy` neither a
+    /// provenance header nor the file name tells.
+    pub fn check_fields(&self, records: &[Record]) -> Result<()> {
+        let mut fields: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for record in records {
+            if let Some(Value::Text(result)) = record.get("result") {
+                fields
+                    .entry(result.as_str())
+                    .or_default()
+                    .extend(record.keys().map(String::as_str));
+            }
+        }
+        for (result, fields) in fields {
+            if let Some(filter) = self
+                .filters
+                .iter()
+                .find(|filter| !fields.contains(filter.field.as_str()))
+            {
+                return Err(Error::Plot(format!(
+                    "{} has no {} to filter on, neither in a provenance header nor from its name",
+                    result, filter.field
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Filters, groups and aggregates `records` into the lines to draw.
+    pub fn lines(&self, records: &[Record]) -> Vec<Line> {
+        let records: Vec<&Record> = records
+            .iter()
+            .filter(|record| self.filters.iter().all(|filter| filter.accepts(record)))
+            .collect();
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut lines = Vec::new();
+        for series in &self.series {
+            let mut groups: BTreeMap<Vec<Value>, Vec<(f64, f64)>> = BTreeMap::new();
+            for record in &records {
+                let point = match (
+                    number(record, &self.x.metric),
+                    number(record, &series.metric),
+                ) {
+                    (Some(x), Some(y)) => (self.bin(x), y),
+                    _ => continue,
+                };
+                let group: Option<Vec<Value>> = self
+                    .group_by
+                    .iter()
+                    .map(|field| record.get(field).cloned())
+                    .collect();
+                if let Some(group) = group {
+                    groups.entry(group).or_default().push(point);
+                }
+            }
+            let label = series.label.as_ref().unwrap_or(&series.metric);
+            for (group, points) in groups {
+                let label = if group.is_empty() {
+                    label.clone()
+                } else {
+                    let keys: Vec<String> = self
+                        .group_by
+                        .iter()
+                        .zip(&group)
+                        .map(|(field, value)| format!("{}={}", field, value))
+                        .collect();
+                    format!("{} ({})", label, keys.join(", "))
+                };
+                lines.push(Line {
+                    label,
+                    points: self.aggregate_points(points, &mut rng),
+                });
+            }
+        }
+        lines
+    }
+
+    fn bin(&self, x: f64) -> f64 {
+        match self.x.bin {
+            Some(width) if width > 0.0 => (x / width).floor() * width,
+            _ => x,
+        }
+    }
+
+    fn aggregate_points(&self, points: Vec<(f64, f64)>, rng: &mut ChaCha8Rng) -> Vec<Point> {
+        if self.aggregate == Aggregate::None {
+            return points
+                .into_iter()
+                .map(|(x, y)| Point {
+                    x,
+                    y,
+                    n: 1,
+                    interval: None,
+                    samples: vec![y],
+                })
+                .collect();
+        }
+        let mut by_x: BTreeMap<N64, Vec<f64>> = BTreeMap::new();
+        for (x, y) in points {
+            by_x.entry(x.into()).or_default().push(y);
+        }
+        by_x.into_iter()
+            .map(|(x, ys)| {
+                let interval = match self.interval {
+                    None => None,
+                    Some(Interval::Stderr) => Some(standard_error(&ys)),
+                    Some(Interval::Bootstrap) => Some(bootstrap(
+                        self.aggregate,
+                        &ys,
+                        self.confidence,
+                        self.resamples,
+                        rng,
+                    )),
+                };
+                Point {
+                    x: x.into_inner(),
+                    y: aggregate(self.aggregate, &ys),
+                    n: ys.len(),
+                    interval,
+                    samples: ys,
+                }
+            })
+            .collect()
+    }
+
+    /// Draws `lines` as an SVG at `plot_path`.
+    pub fn draw(&self, plot_path: &str, lines: &[Line]) -> Result<()> {
+        self.draw_svg(plot_path, lines)
+            .map_err(|why| Error::Plot(why.to_string()))
+    }
+
+    fn draw_svg(
+        &self,
+        plot_path: &str,
+        lines: &[Line],
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let points = lines.iter().flat_map(|line| line.points.iter());
+        let [x_min, x_max] = self
+            .x
+            .range
+            .unwrap_or_else(|| padded_range(points.clone().map(|point| point.x), false));
+        let [y_min, y_max] = self.y.range.unwrap_or_else(|| {
+            padded_range(
+                points.flat_map(|point| {
+                    let (lo, hi) = point.interval.unwrap_or((point.y, point.y));
+                    point.samples.iter().copied().chain(vec![point.y, lo, hi])
+                }),
+                true,
+            )
+        });
+        let root = SVGBackend::new(plot_path, (516, 480)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(&self.title, ("Times", 18))
+            .margin(10)
+            .margin_right(20)
+            .x_label_area_size(50)
+            .y_label_area_size(50)
+            .build_cartesian_2d(x_min..x_max, y_min..y_max)?;
+        chart
+            .configure_mesh()
+            .x_label_style(("Times", 16))
+            .y_label_style(("Times", 16))
+            .x_label_formatter(&|x| format!("{}", x))
+            .x_desc(self.x.label.as_ref().unwrap_or(&self.x.metric))
+            .y_desc(self.y.label.as_deref().unwrap_or(""))
+            .draw()?;
+        let legend = lines.len() > 1 || self.series.iter().any(|s| s.label.is_some());
+        // box and violin plots of different lines sit side by side within one x slot
+        let slot = lines
+            .iter()
+            .flat_map(|line| line.points.windows(2).map(|w| w[1].x - w[0].x))
+            .fold(self.x.bin.unwrap_or(x_max - x_min), f64::min)
+            * 0.8;
+        let width = slot / lines.len().max(1) as f64;
+        for (i, line) in lines.iter().enumerate() {
+            let color = if legend {
+                Palette99::pick(i).to_rgba()
+            } else {
+                BLACK.to_rgba()
+            };
+            let stroke = ShapeStyle::from(&color).stroke_width(1);
+            if self.style == Style::Line {
+                let band: Vec<(f64, f64)> = line
+                    .points
+                    .iter()
+                    .filter_map(|point| point.interval.map(|(lo, _)| (point.x, lo)))
+                    .chain(
+                        line.points
+                            .iter()
+                            .rev()
+                            .filter_map(|point| point.interval.map(|(_, hi)| (point.x, hi))),
+                    )
+                    .collect();
+                if !band.is_empty() {
+                    chart.draw_series(std::iter::once(Polygon::new(band, &color.mix(0.2))))?;
+                }
+            }
+            if self.style == Style::Points {
+                chart.draw_series(line.points.iter().filter_map(|point| {
+                    point.interval.map(|(lo, hi)| {
+                        ErrorBar::new_vertical(point.x, lo, point.y, hi, stroke.clone(), 6)
+                    })
+                }))?;
+            }
+            let offset = (i as f64 - (lines.len() - 1) as f64 / 2.0) * width;
+            let drawn = match self.style {
+                Style::Line => chart.draw_series(LineSeries::new(
+                    line.points.iter().map(|point| (point.x, point.y)),
+                    stroke.clone(),
+                ))?,
+                Style::Points => chart.draw_series(PointSeries::of_element(
+                    line.points.iter().map(|point| (point.x, point.y)),
+                    1,
+                    ShapeStyle::from(&color).filled(),
+                    &|coord, size, style| {
+                        EmptyElement::at(coord) + Circle::new((0, 0), size, style)
+                    },
+                ))?,
+                Style::Box => {
+                    for point in &line.points {
+                        let x = point.x + offset;
+                        let [low, q1, _, q3, high] = box_stats(&point.samples);
+                        chart.draw_series(vec![
+                            PathElement::new(vec![(x, low), (x, q1)], stroke.clone()),
+                            PathElement::new(vec![(x, q3), (x, high)], stroke.clone()),
+                        ])?;
+                        chart.draw_series(std::iter::once(Rectangle::new(
+                            [(x - width * 0.4, q1), (x + width * 0.4, q3)],
+                            stroke.clone(),
+                        )))?;
+                    }
+                    chart.draw_series(line.points.iter().map(|point| {
+                        let x = point.x + offset;
+                        let median = box_stats(&point.samples)[2];
+                        PathElement::new(
+                            vec![(x - width * 0.4, median), (x + width * 0.4, median)],
+                            stroke.clone(),
+                        )
+                    }))?
+                }
+                Style::Violin => chart.draw_series(line.points.iter().map(|point| {
+                    let x = point.x + offset;
+                    let outline = violin_outline(&point.samples, width * 0.45);
+                    let mut shape: Vec<(f64, f64)> =
+                        outline.iter().map(|&(y, half)| (x - half, y)).collect();
+                    shape.extend(outline.iter().rev().map(|&(y, half)| (x + half, y)));
+                    Polygon::new(shape, &color.mix(0.6))
+                }))?,
+            };
+            if legend {
+                let color = color.clone();
+                drawn
+                    .label(&line.label)
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &color));
+            }
+            if self.counts {
+                let x_offset = match self.style {
+                    Style::Box | Style::Violin => offset,
+                    _ => 0.0,
+                };
+                chart.draw_series(line.points.iter().map(|point| {
+                    let top = match self.style {
+                        Style::Box | Style::Violin => box_stats(&point.samples)[4],
+                        _ => point.interval.map_or(point.y, |(_, hi)| hi),
+                    };
+                    EmptyElement::at((point.x + x_offset, top))
+                        + Text::new(
+                            format!("n={}", point.n),
+                            (-8, -14),
+                            ("Times", 10).into_font().color(&color),
+                        )
+                }))?;
+            }
+        }
+        if legend {
+            chart
+                .configure_series_labels()
+                .background_style(&WHITE.mix(0.8))
+                .border_style(&BLACK)
+                .position(SeriesLabelPosition::UpperRight)
+                .draw()?;
+        }
+        Ok(())
+    }
+}
+
+/// Flattens one result file into records, one per file or one per attempt.
+pub fn read_records(results: ResultKind, level: Level, path: &str) -> Result<Vec<Record>> {
+    let (mut records, provenance): (Vec<Record>, _) = match results {
+        ResultKind::Bold => {
+            let result: BoldExperimentResult = read_yaml(path)?;
+            let records = match level {
+                Level::Experiment => bold_experiment_record(&result).into_iter().collect(),
+                Level::Attempt => result.attempts.iter().map(bold_attempt_record).collect(),
+            };
+            (records, result.provenance)
+        }
+        ResultKind::Cautious => {
+            let result: CautiousExperimentResult = read_yaml(path)?;
+            let records = match level {
+                Level::Experiment => cautious_experiment_record(&result).into_iter().collect(),
+                Level::Attempt => result
+                    .attempts
+                    .iter()
+                    .map(cautious_attempt_record)
+                    .collect(),
+            };
+            (records, result.provenance)
+        }
+    };
+    for record in &mut records {
+        record.insert("result".to_string(), Value::Text(path.to_string()));
+        match &provenance {
+            Some(provenance) => provenance_fields(record, provenance),
+            None => legacy_fields(record, path),
+        }
+    }
+    Ok(records)
+}
+
+// Results from before the provenance header: the strategy as far as the file name tells, e.g.
+// `bold_kahead_5.yaml`, and k as far as the announcements the attempts saw do.
+fn legacy_fields(record: &mut Record, path: &str) {
+    let name = Path::new(path)
+        .file_name()
+        .map_or(path.into(), |name| name.to_string_lossy());
+    let strategy = match ["kahead", "kgrouped", "robust"]
+        .iter()
+        .find(|strategy| name.contains(*strategy))
+    {
+        Some(strategy) => *strategy,
+        None => return,
+    };
+    record.insert("strategy".to_string(), Value::Text(strategy.to_string()));
+    let lookahead = match strategy {
+        "kahead" => number(record, "min_lookahead"),
+        "kgrouped" => number(record, "min_inter_announcement_time"),
+        _ => None,
+    };
+    if let Some(lookahead) = lookahead {
+        record.insert("lookahead".to_string(), Value::Number(lookahead));
+    }
+}
+
+fn number(record: &Record, field: &str) -> Option<f64> {
+    record.get(field).and_then(Value::number)
+}
+
+fn aggregate(how: Aggregate, ys: &[f64]) -> f64 {
+    let n = ys.len() as f64;
+    match how {
+        Aggregate::Mean => ys.iter().sum::<f64>() / n,
+        Aggregate::Median => quantile(&sorted(ys), 0.5),
+        Aggregate::Sum => ys.iter().sum(),
+        Aggregate::Count => n,
+        Aggregate::Min => ys.iter().copied().fold(f64::INFINITY, f64::min),
+        Aggregate::Max => ys.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        Aggregate::None => unreachable!(),
+    }
+}
+
+fn sorted(ys: &[f64]) -> Vec<f64> {
+    let mut ys = ys.to_vec();
+    ys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    ys
+}
+
+// linear interpolation between closest ranks of sorted values
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let rank = q * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+fn standard_error(ys: &[f64]) -> (f64, f64) {
+    let n = ys.len() as f64;
+    let mean = ys.iter().sum::<f64>() / n;
+    if ys.len() < 2 {
+        return (mean, mean);
+    }
+    let variance = ys.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let se = (variance / n).sqrt();
+    (mean - se, mean + se)
+}
+
+fn bootstrap(
+    how: Aggregate,
+    ys: &[f64],
+    confidence: f64,
+    resamples: usize,
+    rng: &mut ChaCha8Rng,
+) -> (f64, f64) {
+    let stats: Vec<f64> = (0..resamples)
+        .map(|_| {
+            let resample: Vec<f64> = (0..ys.len())
+                .map(|_| ys[rng.gen_range(0..ys.len())])
+                .collect();
+            aggregate(how, &resample)
+        })
+        .collect();
+    let stats = sorted(&stats);
+    let tail = (1.0 - confidence) / 2.0;
+    (quantile(&stats, tail), quantile(&stats, 1.0 - tail))
+}
+
+// whisker low, first quartile, median, third quartile, whisker high
+fn box_stats(samples: &[f64]) -> [f64; 5] {
+    let ys = sorted(samples);
+    let (q1, median, q3) = (quantile(&ys, 0.25), quantile(&ys, 0.5), quantile(&ys, 0.75));
+    let reach = 1.5 * (q3 - q1);
+    let low = ys.iter().copied().find(|&y| y >= q1 - reach).unwrap_or(q1);
+    let high = ys
+        .iter()
+        .rev()
+        .copied()
+        .find(|&y| y <= q3 + reach)
+        .unwrap_or(q3);
+    [low, q1, median, q3, high]
+}
+
+// (y, half width) pairs of a gaussian kernel density estimate, widest at `max_half`
+fn violin_outline(samples: &[f64], max_half: f64) -> Vec<(f64, f64)> {
+    let ys = sorted(samples);
+    let (min, max) = (ys[0], ys[ys.len() - 1]);
+    let n = ys.len() as f64;
+    let mean = ys.iter().sum::<f64>() / n;
+    let sd = (ys.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / n).sqrt();
+    // silverman's rule of thumb
+    let bandwidth = 1.06 * sd * n.powf(-0.2);
+    if bandwidth <= 0.0 {
+        return vec![(min, max_half), (max, max_half)];
+    }
+    let steps = 40;
+    let density: Vec<(f64, f64)> = (0..=steps)
+        .map(|i| {
+            let y = min + (max - min) * i as f64 / steps as f64;
+            let d: f64 = ys
+                .iter()
+                .map(|s| (-0.5 * ((y - s) / bandwidth).powi(2)).exp())
+                .sum();
+            (y, d)
+        })
+        .collect();
+    let peak = density.iter().map(|&(_, d)| d).fold(0.0, f64::max);
+    density
+        .into_iter()
+        .map(|(y, d)| (y, max_half * d / peak))
+        .collect()
+}
+
+// data range with some headroom, starting at zero for ratios and counts
+fn padded_range(values: impl Iterator<Item = f64>, from_zero: bool) -> [f64; 2] {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+    if min > max {
+        return [0.0, 1.0];
+    }
+    let min = if from_zero { min.min(0.0) } else { min };
+    let pad = if max > min { (max - min) * 0.1 } else { 1.0 };
+    [if from_zero { min } else { min - pad }, max + pad]
+}
+
+fn flag(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn announcement_fields(
+    record: &mut Record,
+    min_inter_announcement_time: usize,
+    min_lookahead: usize,
+    avg_lookahead: Option<N64>,
+) {
+    record.insert(
+        "min_inter_announcement_time".to_string(),
+        Value::Number(min_inter_announcement_time as f64),
+    );
+    record.insert(
+        "min_lookahead".to_string(),
+        Value::Number(min_lookahead as f64),
+    );
+    if let Some(avg_lookahead) = avg_lookahead {
+        record.insert(
+            "avg_lookahead".to_string(),
+            Value::Number(avg_lookahead.into_inner()),
+        );
+    }
+}
+
+// the header's fields under the same names, flattening the file digests into `<name>` and
+// `<name>_sha256`
+fn provenance_fields(record: &mut Record, provenance: &Provenance) {
+    let mut text = |field: &str, text: &str| {
+        record.insert(field.to_string(), Value::Text(text.to_string()));
+    };
+    text("instance", &provenance.instance.path);
+    text("instance_sha256", &provenance.instance.sha256);
+    text("solution", &provenance.solution.path);
+    text("solution_sha256", &provenance.solution.sha256);
+    text("strategy", &provenance.strategy);
+    if let Some(custom) = &provenance.custom_announcements {
+        text("custom_announcements", &custom.path);
+        text("custom_announcements_sha256", &custom.sha256);
+    }
+    text("attacker", &provenance.attacker);
+    text("version", &provenance.version);
+    if let Some(sensing) = &provenance.sensing {
+        text("liar_behavior", sensing.liar_behavior.name());
+    }
+    if let Some(response) = &provenance.response {
+        text("response", response.strategy.name());
+        if let Some(robot) = &response.security_robot {
+            text("security_robot", robot);
+        }
+    }
+    let mut number = |field: &str, number: f64| {
+        record.insert(field.to_string(), Value::Number(number));
+    };
+    if let Some(lookahead) = provenance.lookahead {
+        number("lookahead", lookahead as f64);
+    }
+    if let Some(mitigation) = provenance.mitigation {
+        number("mitigation", flag(mitigation));
+    }
+    if let Some(seconds) = provenance.attempt_timeout {
+        number("attempt_timeout", seconds as f64);
+    }
+    if let Some(iterations) = provenance.attempt_iterations {
+        number("attempt_iterations", iterations as f64);
+    }
+    if let Some(sensing) = &provenance.sensing {
+        number("detection_probability", sensing.detection_probability);
+        number("false_positive_rate", sensing.false_positive_rate);
+        number("liars", sensing.liars as f64);
+        number("quorum", sensing.quorum as f64);
+        number("sensing_runs", sensing.runs as f64);
+        number("seed", sensing.seed as f64);
+        number("confidence", sensing.confidence);
+    }
+    if let Some(channel) = &provenance.channel {
+        number("drop_probability", channel.drop_probability);
+        number("latency", channel.latency as f64);
+        if let Some(range) = channel.range {
+            number("range", range as f64);
+        }
+    }
+    if let Some(cross_checks) = provenance.cross_checks {
+        number("cross_checks", flag(cross_checks));
+    }
+    if let Some(response) = &provenance.response {
+        number("responders", response.responders as f64);
+    }
+    number("attackers_sampled", provenance.attackers_sampled as f64);
+    number("targets_sampled", provenance.targets_sampled as f64);
+    if let Some(seconds) = provenance.wall_time {
+        number("wall_time", seconds);
+    }
+}
+
+fn target_fields(record: &mut Record, safe: &Coordinate, max_inter_observation_time: usize) {
+    record.insert("safe_x".to_string(), Value::Number(safe.x as f64));
+    record.insert("safe_y".to_string(), Value::Number(safe.y as f64));
+    record.insert(
+        "max_inter_observation_time".to_string(),
+        Value::Number(max_inter_observation_time as f64),
+    );
+}
+
+fn bold_attempt_record(attempt: &BoldAttemptResult) -> Record {
+    let mut record = Record::new();
+    record.insert(
+        "attacker_name".to_string(),
+        Value::Text(attempt.attacker_name.clone()),
+    );
+    announcement_fields(
+        &mut record,
+        attempt.min_inter_announcement_time,
+        attempt.min_lookahead,
+        attempt.avg_lookahead,
+    );
+    target_fields(
+        &mut record,
+        &attempt.safe,
+        attempt.max_inter_observation_time,
+    );
+    record.insert(
+        "dangerous".to_string(),
+        Value::Number(flag(attempt.dangerous)),
+    );
+    record.insert(
+        "detected".to_string(),
+        Value::Number(flag(attempt.detected)),
+    );
+    record.insert(
+        "attempted".to_string(),
+        Value::Number(flag(attempt.attempted())),
+    );
+    record.insert(
+        "max_deviated_dist".to_string(),
+        Value::Number(attempt.max_deviated_dist as f64),
+    );
+    // per-attempt counterparts of miss_rate and false_alarm_rate
+    if attempt.dangerous {
+        record.insert("miss".to_string(), Value::Number(flag(!attempt.detected)));
+    } else {
+        record.insert(
+            "false_alarm".to_string(),
+            Value::Number(flag(attempt.detected)),
+        );
+    }
+    // and of their sensed counterparts, as the share of the attempt's runs
+    if let Some(sensed) = &attempt.sensed {
+        let share = |count: usize| Value::Number(count as f64 / sensed.runs as f64);
+        if attempt.dangerous {
+            record.insert(
+                "sensed_miss".to_string(),
+                share(sensed.runs - sensed.detected),
+            );
+        } else {
+            record.insert("sensed_false_alarm".to_string(), share(sensed.detected));
+        }
+    }
+    if let Some(equivocation) = &attempt.equivocation {
+        record.insert(
+            "equivocation_recipients".to_string(),
+            Value::Number(equivocation.recipients as f64),
+        );
+        record.insert(
+            "caught".to_string(),
+            Value::Number(flag(equivocation.caught_at.is_some())),
+        );
+        if let Some(caught) = equivocation.caught_before_target() {
+            record.insert(
+                "caught_before_target".to_string(),
+                Value::Number(flag(caught)),
+            );
+        }
+    }
+    if let Some(response) = attempt.response.as_ref().filter(|_| attempt.dangerous) {
+        record.insert(
+            "stopped".to_string(),
+            Value::Number(flag(response.stopped())),
+        );
+    }
+    record
+}
+
+fn bold_experiment_record(result: &BoldExperimentResult) -> Option<Record> {
+    // each experiment result has the same map and announcements
+    let first = result.attempts.first()?;
+    let mut record = Record::new();
+    announcement_fields(
+        &mut record,
+        first.min_inter_announcement_time,
+        first.min_lookahead,
+        first.avg_lookahead,
+    );
+    record.insert(
+        "attempts".to_string(),
+        Value::Number(result.attempts.len() as f64),
+    );
+    record.insert(
+        "attack_success_rate".to_string(),
+        Value::Number(result.attack_success_rate()),
+    );
+    record.insert(
+        "attack_attempt_rate".to_string(),
+        Value::Number(result.attack_attempt_rate()),
+    );
+    if let Some(miss_rate) = result.miss_rate() {
+        record.insert("miss_rate".to_string(), Value::Number(miss_rate));
+    }
+    if let Some(false_alarm_rate) = result.false_alarm_rate() {
+        record.insert(
+            "false_alarm_rate".to_string(),
+            Value::Number(false_alarm_rate),
+        );
+    }
+    if let Some(rate) = result.caught_before_target_rate() {
+        record.insert("caught_before_target_rate".to_string(), Value::Number(rate));
+    }
+    if let Some(rate) = result.stop_rate() {
+        record.insert("stop_rate".to_string(), Value::Number(rate));
+    }
+    if let Some(sensing) = result.provenance.as_ref().and_then(|p| p.sensing.as_ref()) {
+        let confidence = sensing.confidence;
+        if let Some(estimate) = result.sensed_miss_rate(confidence) {
+            estimate_fields(&mut record, "sensed_miss_rate", &estimate);
+        }
+        if let Some(estimate) = result.sensed_false_alarm_rate(confidence) {
+            estimate_fields(&mut record, "sensed_false_alarm_rate", &estimate);
+        }
+    }
+    Some(record)
+}
+
+// `<name>` with its interval as `<name>_low` and `<name>_high`
+fn estimate_fields(record: &mut Record, name: &str, estimate: &Estimate) {
+    record.insert(name.to_string(), Value::Number(estimate.rate));
+    record.insert(format!("{}_low", name), Value::Number(estimate.interval.0));
+    record.insert(format!("{}_high", name), Value::Number(estimate.interval.1));
+}
+
+fn cautious_attempt_record(attempt: &CautiousAttemptResult) -> Record {
+    let mut record = Record::new();
+    record.insert(
+        "attacker_name".to_string(),
+        Value::Text(attempt.attacker_name.clone()),
+    );
+    announcement_fields(
+        &mut record,
+        attempt.min_inter_announcement_time,
+        attempt.min_lookahead,
+        attempt.avg_lookahead,
+    );
+    target_fields(
+        &mut record,
+        &attempt.safe,
+        attempt.max_inter_observation_time,
+    );
+    // unknown attempts count neither way in the secure rate
+    let unknown = attempt.verdict == CautiousVerdict::Unknown;
+    if !unknown {
+        record.insert(
+            "secured".to_string(),
+            Value::Number(flag(attempt.verdict == CautiousVerdict::Secured)),
+        );
+    }
+    record.insert("unknown".to_string(), Value::Number(flag(unknown)));
+    record
+}
+
+fn cautious_experiment_record(result: &CautiousExperimentResult) -> Option<Record> {
+    let first = result.attempts.first()?;
+    let mut record = Record::new();
+    announcement_fields(
+        &mut record,
+        first.min_inter_announcement_time,
+        first.min_lookahead,
+        first.avg_lookahead,
+    );
+    record.insert(
+        "attempts".to_string(),
+        Value::Number(result.attempts.len() as f64),
+    );
+    if let Some(secure_rate) = result.secure_rate() {
+        record.insert("secure_rate".to_string(), Value::Number(secure_rate));
+    }
+    if let Some(unknown_rate) = result.unknown_rate() {
+        record.insert("unknown_rate".to_string(), Value::Number(unknown_rate));
+    }
+    Some(record)
+}
+
+// parses every result file listed on stdin, with a progress bar
+pub(crate) fn read_stdin_records(results: ResultKind, level: Level) -> Result<Vec<Record>> {
+    let outputs: Vec<String> = io::stdin()
+        .lock()
+        .lines()
+        .collect::<io::Result<_>>()
+        .map_err(|source| Error::Io {
+            path: "<stdin>".to_string(),
+            source,
+        })?;
+    let pb = ProgressBar::new(outputs.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("[{eta:>5}->{elapsed:>5}] [{wide_bar}] {pos:>7}/{len:>7}")
+            .progress_chars("=> "),
+    );
+    println!("parsing output files...");
+    let mut records = Vec::new();
+    for output_path in outputs.iter().progress_with(pb) {
+        records.extend(read_records(results, level, output_path)?);
+    }
+    Ok(records)
+}
+
+/// Reads result paths from stdin and renders `plot`, a built-in name or a spec path, to
+/// `plot_path`.
+pub fn generate_plots(plot: &str, plot_path: &str, force: bool) -> Result<()> {
+    let spec = PlotSpec::load(plot)?;
+    println!("generating {} to \"{}\"", plot, plot_path);
+    println!("force: {}", force);
+    touch(plot_path, force)?;
+    let records = read_stdin_records(spec.results, spec.level)?;
+    spec.check_fields(&records)?;
+    println!("plotting...");
+    let lines = spec.lines(&records);
+    let name = Path::new(plot)
+        .file_stem()
+        .map_or(plot.into(), |stem| stem.to_string_lossy());
+    for line in &lines {
+        let points: Vec<String> = line
+            .points
+            .iter()
+            .map(|point| match point.interval {
+                Some((lo, hi)) => format!(
+                    "({}, {}, n={}, [{}, {}])",
+                    point.x, point.y, point.n, lo, hi
+                ),
+                None => format!("({}, {}, n={})", point.x, point.y, point.n),
+            })
+            .collect();
+        println!(
+            "DATA {} [{}] {}: [{}]",
+            name,
+            line.label,
+            plot_path,
+            points.join(", ")
+        );
+    }
+    spec.draw(plot_path, &lines)?;
+    println!("done.");
+    Ok(())
+}
+
+/// Mean and count of the attempt field `metric` per target cell `(x, y)`.
+pub fn cell_means(records: &[Record], metric: &str) -> BTreeMap<(u16, u16), (f64, usize)> {
+    let mut sums: BTreeMap<(u16, u16), (f64, usize)> = BTreeMap::new();
+    for record in records {
+        if let (Some(x), Some(y), Some(value)) = (
+            number(record, "safe_x"),
+            number(record, "safe_y"),
+            number(record, metric),
+        ) {
+            let entry = sums.entry((x as u16, y as u16)).or_insert((0.0, 0));
+            entry.0 += value;
+            entry.1 += 1;
+        }
+    }
+    sums.into_iter()
+        .map(|(cell, (sum, n))| (cell, (sum / n as f64, n)))
+        .collect()
+}
+
+/// Reads result paths from stdin and colours each target cell of `instance` by the mean of the
+/// attempt field `metric` over all files, e.g. `dangerous` for the attack success rate or
+/// `secured` for the secure rate. Nominal paths from `solution` are drawn on top if given.
+pub fn generate_heatmap(
+    instance: &MapfInstance,
+    solution: Option<&MapfSolution>,
+    results: ResultKind,
+    metric: &str,
+    plot_path: &str,
+    force: bool,
+) -> Result<()> {
+    println!("generating {} heatmap to \"{}\"", metric, plot_path);
+    touch(plot_path, force)?;
+    let records = read_stdin_records(results, Level::Attempt)?;
+    let means = cell_means(&records, metric);
+    if means.is_empty() {
+        return Err(Error::Plot(format!(
+            "no attempt in the results has a {} field",
+            metric
+        )));
+    }
+    for ((x, y), (mean, n)) in &means {
+        println!("DATA ({}, {}): {} n={}", x, y, mean, n);
+    }
+    println!("plotting...");
+    draw_heatmap(instance, solution, metric, &means, plot_path)
+        .map_err(|why| Error::Plot(why.to_string()))?;
+    println!("done.");
+    Ok(())
+}
+
+fn draw_heatmap(
+    instance: &MapfInstance,
+    solution: Option<&MapfSolution>,
+    metric: &str,
+    means: &BTreeMap<(u16, u16), (f64, usize)>,
+    plot_path: &str,
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let (width, height) = (instance.map.dimensions.x, instance.map.dimensions.y);
+    let cell = (600 / width.max(height).max(1) as u32).clamp(6, 30);
+    let map_px = width as u32 * cell + 70;
+    let root =
+        SVGBackend::new(plot_path, (map_px + 110, height as u32 * cell + 90)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let (map_area, bar_area) = root.split_horizontally(map_px);
+    // rates keep a fixed 0..1 scale so heatmaps stay comparable
+    let values = means.values().map(|&(mean, _)| mean);
+    let (lo, hi) = values.fold((0f64, 1f64), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let scale = move |v: f64| heat_color(if hi > lo { (v - lo) / (hi - lo) } else { 0.0 });
+
+    // row 0 at the top, as the maps are usually drawn
+    let h = height as f64;
+    let flip = move |x: f64, y: f64| (x, h - y);
+    let mut chart = ChartBuilder::on(&map_area)
+        .caption(format!("mean {} per target cell", metric), ("Times", 18))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(0f64..width as f64, 0f64..h)?;
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_label_style(("Times", 12))
+        .y_label_style(("Times", 12))
+        .x_label_formatter(&|x| format!("{}", x))
+        .y_label_formatter(&|y| format!("{}", h - y))
+        .draw()?;
+    let cells = (0..width).flat_map(|x| (0..height).map(move |y| (x, y)));
+    chart.draw_series(cells.map(|(x, y)| {
+        let coord = Coordinate { x, y };
+        let fill = match means.get(&(x, y)) {
+            Some(&(mean, _)) => scale(mean),
+            None if instance.map.obstacles.contains(&coord) => RGBColor(90, 90, 90),
+            None => WHITE,
+        };
+        let (x, y) = (x as f64, y as f64);
+        Rectangle::new([flip(x, y), flip(x + 1.0, y + 1.0)], fill.filled())
+    }))?;
+    if let Some(solution) = solution {
+        for (i, agent) in instance.agents.iter().enumerate() {
+            let path = match solution.schedule.get(&agent.name) {
+                Some(path) => path,
+                None => continue,
+            };
+            let color = Palette99::pick(i).to_rgba();
+            let centers: Vec<(f64, f64)> = path
+                .iter()
+                .map(|tc| flip(tc.x as f64 + 0.5, tc.y as f64 + 0.5))
+                .collect();
+            chart.draw_series(
+                centers
+                    .first()
+                    .map(|&start| Circle::new(start, 3, ShapeStyle::from(&color).filled())),
+            )?;
+            chart.draw_series(std::iter::once(PathElement::new(
+                centers,
+                ShapeStyle::from(&color).stroke_width(2),
+            )))?;
+        }
+    }
+
+    let mut bar = ChartBuilder::on(&bar_area)
+        .margin_top(40)
+        .margin_bottom(40)
+        .margin_right(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0f64..1f64, lo..hi)?;
+    bar.configure_mesh()
+        .disable_mesh()
+        .disable_x_axis()
+        .y_label_style(("Times", 12))
+        .draw()?;
+    let steps = 50;
+    bar.draw_series((0..steps).map(|i| {
+        let (from, to) = (
+            lo + (hi - lo) * i as f64 / steps as f64,
+            lo + (hi - lo) * (i + 1) as f64 / steps as f64,
+        );
+        Rectangle::new([(0.0, from), (1.0, to)], scale(from).filled())
+    }))?;
+    Ok(())
+}
+
+// yellow through orange to dark red, for t in 0..=1
+fn heat_color(t: f64) -> RGBColor {
+    const STOPS: [(f64, f64, f64); 3] = [
+        (255.0, 255.0, 204.0),
+        (253.0, 141.0, 60.0),
+        (189.0, 0.0, 38.0),
+    ];
+    let t = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let i = (t.floor() as usize).min(STOPS.len() - 2);
+    let f = t - i as f64;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    let mix = |a: f64, b: f64| (a + (b - a) * f).round() as u8;
+    RGBColor(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::FileDigest;
+    use crate::sensing::{LiarBehavior, SensedDetections, SensingModel};
+    use std::fs;
+
+    fn bold(dangerous: bool, detected: bool, miot: usize) -> BoldAttemptResult {
+        BoldAttemptResult {
+            attacker_name: "agent0".to_string(),
+            safe: Coordinate { x: 1, y: 2 },
+            dangerous,
+            detected,
+            max_deviated_dist: 1,
+            max_inter_observation_time: miot,
+            min_inter_announcement_time: 1,
+            min_lookahead: 5,
+            avg_lookahead: None,
+            sensed: None,
+            equivocation: None,
+            response: None,
+        }
+    }
+
+    fn provenance(strategy: &str, lookahead: Option<usize>) -> Provenance {
+        let digest = |path: &str| FileDigest {
+            path: path.to_string(),
+            sha256: "0".repeat(64),
+        };
+        Provenance {
+            instance: digest("map.yaml"),
+            solution: digest("plan.yaml"),
+            strategy: strategy.to_string(),
+            lookahead,
+            custom_announcements: None,
+            attacker: "bold".to_string(),
+            mitigation: Some(true),
+            attempt_timeout: None,
+            attempt_iterations: None,
+            sensing: None,
+            channel: None,
+            cross_checks: None,
+            response: None,
+            attackers_sampled: 10,
+            targets_sampled: 10,
+            version: "0.1.0".to_string(),
+            wall_time: Some(1.5),
+        }
+    }
+
+    #[test]
+    fn builtin_specs_parse() {
+        for (name, _) in BUILTIN_PLOTS {
+            assert!(PlotSpec::builtin(name).is_some(), "{}", name);
+        }
+        assert_eq!(PlotSpec::builtin("nope"), None);
+    }
+
+    #[test]
+    fn attempt_level_mean_pools_rates() {
+        let spec = PlotSpec::builtin("succ-vs-max-inter-obs").unwrap();
+        let records: Vec<Record> = [
+            bold(true, false, 3),
+            bold(true, true, 3),
+            bold(false, true, 3),
+            bold(true, false, 4),
+            bold(true, false, 50),
+        ]
+        .iter()
+        .map(|attempt| {
+            let mut record = bold_attempt_record(attempt);
+            provenance_fields(&mut record, &provenance("kahead", Some(5)));
+            record
+        })
+        .collect();
+        let lines = spec.lines(&records);
+        let find = |label: &str| {
+            lines
+                .iter()
+                .find(|line| line.label == label)
+                .unwrap()
+                .points
+                .iter()
+                .map(|point| (point.x, point.y))
+                .collect::<Vec<_>>()
+        };
+        // the attempt at inter-observation time 50 is filtered out
+        assert_eq!(
+            find("attacker success ratio"),
+            vec![(3.0, 2.0 / 3.0), (4.0, 1.0)]
+        );
+        assert_eq!(find("miss ratio"), vec![(3.0, 0.5), (4.0, 1.0)]);
+        // no non-dangerous attempt at 4, so no early alarm point there
+        assert_eq!(find("early alarm ratio"), vec![(3.0, 1.0)]);
+    }
+
+    #[test]
+    fn group_by_splits_series() {
+        let spec: PlotSpec = serde_yaml::from_str(
+            "
+title: test
+results: bold
+level: attempt
+x: { metric: max_inter_observation_time }
+series: [{ metric: detected }]
+group_by: [safe_x]
+aggregate: count
+",
+        )
+        .unwrap();
+        let mut other = bold(true, true, 3);
+        other.safe.x = 7;
+        let records: Vec<Record> = [bold(true, true, 3), bold(true, false, 3), other]
+            .iter()
+            .map(bold_attempt_record)
+            .collect();
+        let lines: Vec<_> = spec
+            .lines(&records)
+            .into_iter()
+            .map(|line| {
+                let points: Vec<_> = line.points.iter().map(|p| (p.x, p.y, p.n)).collect();
+                (line.label, points)
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("detected (safe_x=1)".to_string(), vec![(3.0, 2.0, 2)]),
+                ("detected (safe_x=7)".to_string(), vec![(3.0, 1.0, 1)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn intervals_bracket_the_aggregate() {
+        let mut spec = PlotSpec::builtin("succ-vs-max-inter-obs").unwrap();
+        spec.filters.clear();
+        let records: Vec<Record> = (0..40)
+            .map(|i| bold_attempt_record(&bold(i % 4 == 0, false, 3)))
+            .collect();
+        spec.interval = Some(Interval::Stderr);
+        let stderr = spec.lines(&records)[0].points[0].clone();
+        assert_eq!((stderr.y, stderr.n), (0.25, 40));
+        let (lo, hi) = stderr.interval.unwrap();
+        // sample sd of 10 ones and 30 zeros is sqrt(30 / 156)
+        assert!((hi - lo - 2.0 * (30.0f64 / 156.0 / 40.0).sqrt()).abs() < 1e-12);
+
+        spec.interval = Some(Interval::Bootstrap);
+        let first = spec.lines(&records);
+        let (lo, hi) = first[0].points[0].interval.unwrap();
+        assert!(lo < 0.25 && 0.25 < hi && lo >= 0.0 && hi <= 1.0);
+        // seeded, so reruns agree
+        assert_eq!(first, spec.lines(&records));
+
+        spec.aggregate = Aggregate::Count;
+        assert!(spec.check().is_err());
+    }
+
+    #[test]
+    fn box_stats_clip_whiskers_to_outliers() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 5.0, 100.0];
+        let [low, q1, median, q3, high] = box_stats(&samples);
+        assert_eq!((low, q1, median, q3), (1.0, 2.25, 3.5, 4.75));
+        assert_eq!(high, 5.0);
+    }
+
+    #[test]
+    fn cell_means_average_per_target() {
+        let mut elsewhere = bold(false, false, 3);
+        elsewhere.safe = Coordinate { x: 4, y: 0 };
+        let records: Vec<Record> = [bold(true, false, 3), bold(false, false, 9), elsewhere]
+            .iter()
+            .map(bold_attempt_record)
+            .collect();
+        let means = cell_means(&records, "dangerous");
+        assert_eq!(means.len(), 2);
+        assert_eq!(means[&(1, 2)], (0.5, 2));
+        assert_eq!(means[&(4, 0)], (0.0, 1));
+        assert!(cell_means(&records, "secured").is_empty());
+    }
+
+    #[test]
+    fn unknown_verdicts_stay_out_of_the_secure_rate() {
+        // the first two attempts as written before verdicts
+        let attempt = |verdict: &str, x| {
+            format!(
+                "  - attacker_name: agent0\n    safe: {{x: {}, y: 0}}\n    \
+                 max_inter_observation_time: 4\n    min_inter_announcement_time: 1\n    \
+                 min_lookahead: 2\n    avg_lookahead: ~\n    {}\n",
+                x, verdict
+            )
+        };
+        let yaml = format!(
+            "attempts:\n{}{}{}{}",
+            attempt("secured: true", 0),
+            attempt("secured: false", 1),
+            attempt("verdict: secured", 2),
+            attempt("verdict: unknown", 3)
+        );
+        let result: CautiousExperimentResult = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(result.attempts[1].verdict, CautiousVerdict::NotSecured);
+        assert_eq!(result.secure_count(), 2);
+        assert_eq!(result.unknown_count(), 1);
+        assert_eq!(result.secure_rate(), Some(2.0 / 3.0));
+        assert_eq!(result.unknown_rate(), Some(0.25));
+        let records: Vec<Record> = result
+            .attempts
+            .iter()
+            .map(cautious_attempt_record)
+            .collect();
+        let means = cell_means(&records, "secured");
+        assert_eq!(means.len(), 3);
+        assert_eq!(cell_means(&records, "unknown")[&(3, 0)], (1.0, 1));
+        let reread: CautiousExperimentResult =
+            serde_yaml::from_str(&serde_yaml::to_string(&result).unwrap()).unwrap();
+        assert_eq!(reread, result);
+    }
+
+    #[test]
+    fn sensed_rates_pool_the_runs_of_each_kind_of_attempt() {
+        let sensed = |dangerous, detected| BoldAttemptResult {
+            sensed: Some(SensedDetections {
+                runs: 100,
+                detected,
+            }),
+            ..bold(dangerous, true, 3)
+        };
+        let mut provenance = provenance("kahead", Some(3));
+        provenance.sensing = Some(SensingModel {
+            detection_probability: 0.8,
+            false_positive_rate: 0.01,
+            liars: 0,
+            liar_behavior: LiarBehavior::Omit,
+            quorum: 1,
+            runs: 100,
+            seed: 0,
+            confidence: 0.95,
+        });
+        let result = BoldExperimentResult {
+            provenance: Some(provenance),
+            attempts: vec![sensed(true, 90), sensed(true, 70), sensed(false, 5)],
+        };
+        let record = bold_experiment_record(&result).unwrap();
+        let value = |field: &str| number(&record, field).unwrap();
+        assert_eq!(value("miss_rate"), 0.0);
+        assert_eq!(value("sensed_miss_rate"), 0.2);
+        assert!(value("sensed_miss_rate_low") < 0.2 && 0.2 < value("sensed_miss_rate_high"));
+        assert_eq!(value("sensed_false_alarm_rate"), 0.05);
+        let attempt = bold_attempt_record(&result.attempts[1]);
+        assert_eq!(number(&attempt, "sensed_miss"), Some(0.3));
+    }
+
+    #[test]
+    fn provenance_fields_drive_filters_and_groups() {
+        // min_lookahead is 5 in every attempt, only the header knows k
+        let records: Vec<Record> = vec![
+            ("kahead", Some(3), true),
+            ("kahead", Some(7), false),
+            ("kgrouped", Some(3), true),
+            ("robust", None, true),
+        ]
+        .into_iter()
+        .map(|(strategy, lookahead, dangerous)| {
+            let result = BoldExperimentResult {
+                provenance: None,
+                attempts: vec![bold(dangerous, false, 3)],
+            };
+            let mut record = bold_experiment_record(&result).unwrap();
+            provenance_fields(&mut record, &provenance(strategy, lookahead));
+            record
+        })
+        .collect();
+        let spec = PlotSpec::builtin("succ-vs-kahead").unwrap();
+        let success: Vec<(f64, f64)> = spec.lines(&records)[0]
+            .points
+            .iter()
+            .map(|point| (point.x, point.y))
+            .collect();
+        assert_eq!(success, vec![(3.0, 1.0), (7.0, 0.0)]);
+
+        let mut spec = PlotSpec::builtin("succ-vs-kgrouped").unwrap();
+        spec.filters.clear();
+        spec.aggregate = Aggregate::Count;
+        spec.group_by = vec!["strategy".to_string()];
+        let labels: Vec<String> = spec.lines(&records)[..3]
+            .iter()
+            .map(|line| line.label.clone())
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                "attacker success ratio (strategy=kahead)",
+                "attacker success ratio (strategy=kgrouped)",
+                "attacker success ratio (strategy=robust)",
+            ]
+        );
+
+        // results from before the header still load, without those fields
+        let legacy: BoldExperimentResult = serde_yaml::from_str("attempts: []").unwrap();
+        assert_eq!(legacy.provenance, None);
+    }
+
+    #[test]
+    fn results_without_a_header_take_the_strategy_from_their_name() {
+        let legacy = BoldExperimentResult {
+            provenance: None,
+            attempts: vec![bold(true, false, 3)],
+        };
+        let write = |name: &str| {
+            let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+            fs::write(&path, serde_yaml::to_string(&legacy).unwrap()).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        let (named, unnamed) = (write("bold_kahead_5.yaml"), write("bold.yaml"));
+        let spec = PlotSpec::builtin("succ-vs-kahead").unwrap();
+        let records = read_records(ResultKind::Bold, Level::Experiment, &named).unwrap();
+        assert_eq!(
+            spec.check_fields(&records).map_err(|e| e.to_string()),
+            Ok(())
+        );
+        // k from the lookahead every attempt saw
+        assert_eq!(spec.lines(&records)[0].points[0].x, 5.0);
+
+        let records = read_records(ResultKind::Bold, Level::Attempt, &unnamed).unwrap();
+        fs::remove_file(named).unwrap();
+        fs::remove_file(&unnamed).unwrap();
+        let spec = PlotSpec::builtin("succ-vs-max-inter-obs").unwrap();
+        assert_eq!(
+            spec.check_fields(&records).map_err(|e| e.to_string()),
+            Err(format!(
+                "plotting error: {} has no strategy to filter on, neither in a provenance \
+                 header nor from its name",
+                unnamed
+            ))
+        );
+    }
+}
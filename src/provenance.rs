@@ -0,0 +1,78 @@
+//! Where a result file came from: its inputs, the configuration of the run and how long it
+//! took. Written as the header of every result file, see [`Provenance`].
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+
+use crate::channel::Channel;
+use crate::error::{Error, Result};
+use crate::response::Response;
+use crate::sensing::SensingModel;
+
+/// A file read by a run and the SHA-256 of its content when it was read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileDigest {
+    pub path: String,
+    pub sha256: String,
+}
+
+impl FileDigest {
+    pub fn of(path: &str) -> Result<FileDigest> {
+        let content = fs::read(path).map_err(|source| Error::Io {
+            path: path.to_string(),
+            source,
+        })?;
+        Ok(FileDigest {
+            path: path.to_string(),
+            sha256: format!("{:x}", Sha256::digest(&content)),
+        })
+    }
+}
+
+/// The configuration an experiment result was produced with, so plots and summaries can group
+/// by it rather than infer it from the attempts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub instance: FileDigest,
+    pub solution: FileDigest,
+    /// `kahead`, `kgrouped`, `robust` or `custom`.
+    pub strategy: String,
+    /// k for `kahead` and `kgrouped`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lookahead: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_announcements: Option<FileDigest>,
+    /// `bold` or `cautious`.
+    pub attacker: String,
+    /// Whether co-observations were checked, bold attackers only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mitigation: Option<bool>,
+    /// Seconds a cautious attempt may take before it is recorded as unknown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt_timeout: Option<u64>,
+    /// Iterations a cautious attempt may take before it is recorded as unknown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt_iterations: Option<usize>,
+    /// How announcements were delivered, if not to everyone at once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Channel>,
+    /// Imperfect co-observations, bold attackers only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensing: Option<SensingModel>,
+    /// Whether defenders compared what they were told, equivocating bold attackers only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cross_checks: Option<bool>,
+    /// How the defenders responded to a detection, bold attackers only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Response>,
+    /// Number of agents tried as the attacker, the first ones of the instance.
+    pub attackers_sampled: usize,
+    /// Number of obstacles tried as the target, spread over the map's obstacles in row-major
+    /// order.
+    pub targets_sampled: usize,
+    pub version: String,
+    /// Seconds the analysis took, left out by `--no-wall-time` so reruns write identical files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wall_time: Option<f64>,
+}
@@ -0,0 +1,338 @@
+//! Time-stepped animations of a single bold attempt, see [`render_replay`].
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::experiments::{BoldEvent, BoldTrace};
+use crate::inputs::{Announcements, Coordinate, MapfInstance, MapfSolution, TimedCoordinate};
+use crate::loader::touch;
+
+/// Everything a replay frame shows. `solution` should already be padded with
+/// [`extend_stay_in_place`](crate::extend_stay_in_place), as for the analyses.
+pub struct Scene<'a> {
+    pub instance: &'a MapfInstance,
+    pub solution: &'a MapfSolution,
+    pub announcements: &'a Announcements,
+    pub attacker: &'a str,
+    pub safe: Coordinate,
+    pub trace: &'a BoldTrace,
+}
+
+/// Renders one frame per step of `scene.trace` to `output`: an animated GIF if it ends in
+/// `.gif`, otherwise a directory of `frame_0000.svg`, `frame_0001.svg`, ...
+pub fn render_replay(scene: &Scene, output: &str, delay_ms: u32, force: bool) -> Result<()> {
+    let size = frame_size(scene.instance);
+    let plot_error = |why: String| Error::Plot(format!("{}: {}", output, why));
+    if output.ends_with(".gif") {
+        touch(output, force)?;
+        let root = BitMapBackend::gif(output, size, delay_ms)
+            .map_err(|why| plot_error(why.to_string()))?
+            .into_drawing_area();
+        for step in 0..scene.trace.steps.len() {
+            draw_frame(&root, scene, step).map_err(|why| plot_error(why.to_string()))?;
+            root.present().map_err(|why| plot_error(why.to_string()))?;
+        }
+    } else {
+        if Path::new(output).exists() && !force {
+            return Err(Error::Io {
+                path: output.to_string(),
+                source: std::io::Error::new(std::io::ErrorKind::AlreadyExists, "already exists"),
+            });
+        }
+        fs::create_dir_all(output).map_err(|source| Error::Io {
+            path: output.to_string(),
+            source,
+        })?;
+        for step in 0..scene.trace.steps.len() {
+            let path = Path::new(output).join(format!("frame_{:04}.svg", step));
+            let root = SVGBackend::new(&path, size).into_drawing_area();
+            draw_frame(&root, scene, step).map_err(|why| plot_error(why.to_string()))?;
+            root.present().map_err(|why| plot_error(why.to_string()))?;
+        }
+    }
+    println!("wrote {} frames to {}", scene.trace.steps.len(), output);
+    Ok(())
+}
+
+fn cell_px(instance: &MapfInstance) -> u32 {
+    let dimensions = instance.map.dimensions;
+    (600 / dimensions.x.max(dimensions.y).max(1) as u32).clamp(8, 30)
+}
+
+fn frame_size(instance: &MapfInstance) -> (u32, u32) {
+    let cell = cell_px(instance);
+    let dimensions = instance.map.dimensions;
+    (
+        dimensions.x as u32 * cell + 60,
+        dimensions.y as u32 * cell + 110,
+    )
+}
+
+fn describe(event: &BoldEvent) -> String {
+    match event {
+        BoldEvent::Collision => "DETECTED: collision".to_string(),
+        BoldEvent::Drained => "DETECTED: battery drained".to_string(),
+        BoldEvent::UnexpectedObservation => "DETECTED: unexpected observation".to_string(),
+        BoldEvent::MissedObservation { observer } => {
+            format!("DETECTED: {} missed its co-observation", observer)
+        }
+        BoldEvent::ReachedTarget => "target reached".to_string(),
+        BoldEvent::KnownDeviation => "undetectable path to the target found".to_string(),
+    }
+}
+
+fn is_detection(event: &BoldEvent) -> bool {
+    !matches!(event, BoldEvent::ReachedTarget | BoldEvent::KnownDeviation)
+}
+
+fn draw_frame<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    scene: &Scene,
+    step: usize,
+) -> std::result::Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let instance = scene.instance;
+    let current = &scene.trace.steps[step];
+    let t = current.t;
+    let (width, height) = (instance.map.dimensions.x, instance.map.dimensions.y);
+    // row 0 at the top, as the maps are usually drawn
+    let h = height as f64;
+    let cell = |x: u16, y: u16| {
+        let (x, y) = (x as f64, y as f64);
+        [(x, h - y), (x + 1.0, h - y - 1.0)]
+    };
+    let center = |tc: &TimedCoordinate| (tc.x as f64 + 0.5, h - tc.y as f64 - 0.5);
+    let nominal = |name: &str, t: usize| {
+        let path = &scene.solution.schedule[name];
+        path[t.min(path.len() - 1)]
+    };
+
+    root.fill(&WHITE)?;
+    let (map_area, status_area) = root.split_vertically(height as u32 * cell_px(instance) + 70);
+    let mut chart = ChartBuilder::on(&map_area)
+        .caption(
+            format!(
+                "t = {}: {} heading for ({}, {})",
+                t, scene.attacker, scene.safe.x, scene.safe.y
+            ),
+            ("Times", 18),
+        )
+        .margin(10)
+        .x_label_area_size(20)
+        .y_label_area_size(20)
+        .build_cartesian_2d(0f64..width as f64, 0f64..h)?;
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_label_style(("Times", 10))
+        .y_label_style(("Times", 10))
+        .x_label_formatter(&|x| format!("{}", x))
+        .y_label_formatter(&|y| format!("{}", h - y))
+        .draw()?;
+
+    chart.draw_series(instance.map.obstacles.iter().map(|obstacle| {
+        Rectangle::new(cell(obstacle.x, obstacle.y), RGBColor(90, 90, 90).filled())
+    }))?;
+    chart.draw_series(std::iter::once(Rectangle::new(
+        cell(scene.safe.x, scene.safe.y),
+        RGBColor(255, 200, 0).filled(),
+    )))?;
+
+    let defenders: Vec<(usize, &str)> = instance
+        .agents
+        .iter()
+        .enumerate()
+        .map(|(i, agent)| (i, agent.name.as_str()))
+        .filter(|&(_, name)| name != scene.attacker)
+        .collect();
+    // observation footprints: every defender sees its own and the 4-neighbouring cells
+    for &(i, name) in &defenders {
+        let color = Palette99::pick(i).mix(0.15);
+        let at = nominal(name, t);
+        let footprint = (-1i32..=1)
+            .flat_map(|dx| (-1i32..=1).map(move |dy| (dx, dy)))
+            .filter(|(dx, dy)| dx.abs() + dy.abs() <= 1)
+            .map(|(dx, dy)| (at.x as i32 + dx, at.y as i32 + dy))
+            .filter(|&(x, y)| x >= 0 && y >= 0 && x < width as i32 && y < height as i32);
+        chart.draw_series(
+            footprint.map(|(x, y)| Rectangle::new(cell(x as u16, y as u16), color.filled())),
+        )?;
+    }
+
+    // nominal paths faintly, the part announced at t boldly
+    for (i, agent) in instance.agents.iter().enumerate() {
+        let path = &scene.solution.schedule[&agent.name];
+        let color = if agent.name == scene.attacker {
+            RED.to_rgba()
+        } else {
+            Palette99::pick(i).to_rgba()
+        };
+        chart.draw_series(std::iter::once(PathElement::new(
+            path.iter().map(center).collect::<Vec<_>>(),
+            ShapeStyle::from(&color.mix(0.25)).stroke_width(1),
+        )))?;
+        let until = scene.announcements.schedule[&agent.name]
+            .get(t)
+            .copied()
+            .unwrap_or(t + 1)
+            .min(path.len());
+        if until > t {
+            chart.draw_series(std::iter::once(PathElement::new(
+                path[t..until].iter().map(center).collect::<Vec<_>>(),
+                ShapeStyle::from(&color.mix(0.7)).stroke_width(3),
+            )))?;
+        }
+    }
+    chart.draw_series(defenders.iter().map(|&(i, name)| {
+        Circle::new(
+            center(&nominal(name, t)),
+            5,
+            ShapeStyle::from(&Palette99::pick(i)).filled(),
+        )
+    }))?;
+
+    // the attacker: where it should be, where it went, where it is
+    chart.draw_series(std::iter::once(Circle::new(
+        center(&nominal(scene.attacker, t)),
+        6,
+        ShapeStyle::from(&RED).stroke_width(1),
+    )))?;
+    chart.draw_series(std::iter::once(PathElement::new(
+        scene.trace.steps[..=step]
+            .iter()
+            .map(|s| center(&s.position))
+            .collect::<Vec<_>>(),
+        ShapeStyle::from(&RED).stroke_width(2),
+    )))?;
+    let detected_now = current.events.iter().any(is_detection);
+    chart.draw_series(std::iter::once(Circle::new(
+        center(&current.position),
+        if detected_now { 9 } else { 6 },
+        ShapeStyle::from(&RED).filled(),
+    )))?;
+
+    // status line: this step's events, then whether the attacker was ever caught
+    let detected_at = scene
+        .trace
+        .steps
+        .iter()
+        .take(step + 1)
+        .find(|s| s.events.iter().any(is_detection))
+        .map(|s| s.t);
+    let mut status: Vec<String> = current.events.iter().map(describe).collect();
+    if status.is_empty() {
+        status.push(match detected_at {
+            Some(at) => format!("detected at t = {}", at),
+            None => "undetected".to_string(),
+        });
+    }
+    if detected_now {
+        status_area.fill(&RED.mix(0.15))?;
+    }
+    status_area.draw_text(
+        &status.join("; "),
+        &("Times", 16)
+            .into_font()
+            .color(if detected_at.is_some() { &RED } else { &BLACK }),
+        (20, 10),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::BoldStep;
+    use crate::inputs::Statistics;
+
+    #[test]
+    fn one_frame_per_step_and_the_detection_shows() {
+        let instance: MapfInstance = serde_yaml::from_str(
+            "
+map: {dimensions: [3, 2], obstacles: [[1, 1]]}
+agents:
+  - {name: agent0, start: [0, 0], goal: [2, 0]}
+  - {name: agent1, start: [2, 1], goal: [2, 1]}
+",
+        )
+        .unwrap();
+        let path = |cells: &[(u16, u16)]| -> Vec<TimedCoordinate> {
+            cells
+                .iter()
+                .enumerate()
+                .map(|(t, &(x, y))| TimedCoordinate { x, y, t })
+                .collect()
+        };
+        let solution = MapfSolution {
+            statistics: Statistics {
+                cost: 2,
+                makespan: 2,
+                runtime: 0.0,
+                highLevelExpanded: 0,
+                lowLevelExpanded: 0,
+            },
+            schedule: vec![
+                ("agent0".to_string(), path(&[(0, 0), (1, 0), (2, 0)])),
+                ("agent1".to_string(), path(&[(2, 1); 3])),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let announcements = Announcements {
+            schedule: vec![
+                ("agent0".to_string(), vec![2, 3, 3]),
+                ("agent1".to_string(), vec![2, 3, 3]),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        // agent0 steps up to its target, then is seen off its announced path
+        let trace = BoldTrace {
+            steps: path(&[(0, 0), (0, 1), (1, 0)])
+                .into_iter()
+                .enumerate()
+                .map(|(t, position)| BoldStep {
+                    t,
+                    position,
+                    events: if t == 2 {
+                        vec![BoldEvent::UnexpectedObservation]
+                    } else {
+                        vec![]
+                    },
+                })
+                .collect(),
+        };
+        let scene = Scene {
+            instance: &instance,
+            solution: &solution,
+            announcements: &announcements,
+            attacker: "agent0",
+            safe: Coordinate { x: 0, y: 1 },
+            trace: &trace,
+        };
+        let output = std::env::temp_dir().join(format!("replay-{}", std::process::id()));
+        let output = output.to_str().unwrap();
+        render_replay(&scene, output, 100, false).unwrap();
+        // the directory exists now
+        assert!(render_replay(&scene, output, 100, false).is_err());
+
+        let mut frames: Vec<String> = fs::read_dir(output)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        frames.sort();
+        assert_eq!(
+            frames,
+            vec!["frame_0000.svg", "frame_0001.svg", "frame_0002.svg"]
+        );
+        let frame = |step: usize| {
+            fs::read_to_string(Path::new(output).join(format!("frame_{:04}.svg", step))).unwrap()
+        };
+        let (quiet, detected) = (frame(1), frame(2));
+        fs::remove_dir_all(output).unwrap();
+        assert!(quiet.contains("undetected"));
+        assert!(detected.contains("DETECTED: unexpected observation"));
+    }
+}
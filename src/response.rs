@@ -0,0 +1,398 @@
+//! What the defenders do once they detect a bold attacker: send responders to stop it before it
+//! reaches its target, see [`Response`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+use crate::compiled::{AgentId, Compiled};
+use crate::inputs::{Coordinate, TimedCoordinate};
+
+/// Who responds to a detection and how. Responders drop their plans and move one cell per step
+/// along shortest paths around the obstacles, ignoring the other agents, and know where the
+/// attacker is from the detection on, so the outcome is a best case for the defenders.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    /// Number of defenders nearest the attacker when it is detected that respond, unless a
+    /// `security_robot` does.
+    pub responders: usize,
+    /// Agent that responds alone, whoever is nearest. Responds to no attempt in which it is the
+    /// attacker itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security_robot: Option<String>,
+    #[serde(default)]
+    pub strategy: ResponseStrategy,
+}
+
+/// What the responders head for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseStrategy {
+    /// The attacker, which is contained once a responder can be next to it.
+    #[default]
+    Intercept,
+    /// The target, which is contained once every free cell next to it is held by a responder
+    /// of its own.
+    Cordon,
+}
+
+impl ResponseStrategy {
+    /// As written in result files and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            ResponseStrategy::Intercept => "intercept",
+            ResponseStrategy::Cordon => "cordon",
+        }
+    }
+}
+
+/// How the response to one bold attempt went.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseOutcome {
+    /// Time step the attacker was first detected, if it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_at: Option<usize>,
+    /// Time step the attacker reached the target, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reached_at: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub responders: Vec<String>,
+    /// Time step the responders contained the attacker, if they did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contained_at: Option<usize>,
+}
+
+impl ResponseOutcome {
+    /// Whether the attacker was contained before it reached the target, or without reaching it.
+    pub fn stopped(&self) -> bool {
+        match (self.contained_at, self.reached_at) {
+            (Some(contained), Some(reached)) => contained < reached,
+            (contained, None) => contained.is_some(),
+            (None, Some(_)) => false,
+        }
+    }
+}
+
+impl Response {
+    /// The response to an attacker that went along `trajectory`, from time step 0, and was
+    /// detected at `detected_at`.
+    pub fn contain(
+        &self,
+        compiled: &Compiled,
+        attacker: AgentId,
+        safe: &Coordinate,
+        trajectory: &[TimedCoordinate],
+        detected_at: Option<usize>,
+        reached_at: Option<usize>,
+    ) -> ResponseOutcome {
+        let mut outcome = ResponseOutcome {
+            detected_at,
+            reached_at,
+            responders: Vec::new(),
+            contained_at: None,
+        };
+        let detected_at = match detected_at {
+            Some(t) if t < trajectory.len() => t,
+            _ => return outcome,
+        };
+        let responders = self.responders(compiled, attacker, trajectory[detected_at], detected_at);
+        let obstacles: HashSet<Coordinate> = compiled.obstacles.iter().copied().collect();
+        let distances: Vec<Vec<Option<usize>>> = responders
+            .iter()
+            .map(|&responder| {
+                distances(
+                    compiled,
+                    &obstacles,
+                    compiled.at(responder, detected_at).into(),
+                )
+            })
+            .collect();
+        // steps a responder needs to stand on or next to `cell`
+        let reach = |cell: Coordinate| {
+            let x = compiled.dimensions.x as usize;
+            compiled
+                .neighborhood(cell)
+                .filter_map(|near| {
+                    let i = near.y as usize * x + near.x as usize;
+                    distances.iter().filter_map(|d| d[i]).min()
+                })
+                .min()
+        };
+        outcome.contained_at = match self.strategy {
+            ResponseStrategy::Intercept => (detected_at..trajectory.len()).find(
+                |&t| matches!(reach(trajectory[t].into()), Some(steps) if steps <= t - detected_at),
+            ),
+            ResponseStrategy::Cordon => {
+                cordon(compiled, &obstacles, &distances, *safe).map(|steps| detected_at + steps)
+            }
+        };
+        outcome.responders = responders
+            .iter()
+            .map(|&responder| compiled.name(responder).clone())
+            .collect();
+        outcome
+    }
+
+    // the security robot, or the defenders nearest `position` at `t` by Manhattan distance
+    fn responders(
+        &self,
+        compiled: &Compiled,
+        attacker: AgentId,
+        position: TimedCoordinate,
+        t: usize,
+    ) -> Vec<AgentId> {
+        if let Some(name) = &self.security_robot {
+            return compiled
+                .id(name)
+                .filter(|&robot| robot != attacker)
+                .into_iter()
+                .collect();
+        }
+        let mut defenders: Vec<AgentId> = compiled.defenders(attacker).collect();
+        defenders
+            .sort_by_key(|&defender| (compiled.at(defender, t).manh_dist(&position), defender));
+        defenders.truncate(self.responders);
+        defenders
+    }
+}
+
+// steps until every free cell next to `target` is held by a responder of its own, along the
+// assignment whose longest walk is shortest, if there are enough responders to hold them all
+fn cordon(
+    compiled: &Compiled,
+    obstacles: &HashSet<Coordinate>,
+    distances: &[Vec<Option<usize>>],
+    target: Coordinate,
+) -> Option<usize> {
+    let width = compiled.dimensions.x as usize;
+    // steps[cell][responder]
+    let steps: Vec<Vec<Option<usize>>> = compiled
+        .neighborhood(target)
+        .filter(|&cell| cell != target && !obstacles.contains(&cell))
+        .map(|cell| {
+            let i = cell.y as usize * width + cell.x as usize;
+            distances.iter().map(|d| d[i]).collect()
+        })
+        .collect();
+    let mut bounds: Vec<usize> = steps.iter().flatten().flatten().copied().collect();
+    bounds.push(0);
+    bounds.sort_unstable();
+    bounds.dedup();
+    bounds.into_iter().find(|&bound| {
+        let mut holders = vec![None; distances.len()];
+        (0..steps.len()).all(|cell| {
+            let mut tried = vec![false; distances.len()];
+            hold(&steps, bound, cell, &mut holders, &mut tried)
+        })
+    })
+}
+
+// whether `cell` can be given a responder within `bound` steps, moving the cells held so far
+// to other responders if need be
+fn hold(
+    steps: &[Vec<Option<usize>>],
+    bound: usize,
+    cell: usize,
+    holders: &mut [Option<usize>],
+    tried: &mut [bool],
+) -> bool {
+    for responder in 0..holders.len() {
+        if tried[responder] || steps[cell][responder].is_none_or(|walk| walk > bound) {
+            continue;
+        }
+        tried[responder] = true;
+        let free = match holders[responder] {
+            None => true,
+            Some(other) => hold(steps, bound, other, holders, tried),
+        };
+        if free {
+            holders[responder] = Some(cell);
+            return true;
+        }
+    }
+    false
+}
+
+// steps from `start` to every cell of the map around the obstacles, row-major
+fn distances(
+    compiled: &Compiled,
+    obstacles: &HashSet<Coordinate>,
+    start: Coordinate,
+) -> Vec<Option<usize>> {
+    let width = compiled.dimensions.x as usize;
+    let mut distances = vec![None; width * compiled.dimensions.y as usize];
+    let mut queue = VecDeque::new();
+    distances[start.y as usize * width + start.x as usize] = Some(0);
+    queue.push_back((start, 0));
+    while let Some((cell, steps)) = queue.pop_front() {
+        for next in compiled.neighborhood(cell) {
+            let i = next.y as usize * width + next.x as usize;
+            if distances[i].is_none() && !obstacles.contains(&next) {
+                distances[i] = Some(steps + 1);
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::{MapfInstance, MapfSolution, Statistics};
+    use crate::utils::compute_kahead_announcements;
+
+    // a 7 by 1 corridor with the target at its east end, the attacker starting next to it
+    fn compiled() -> Compiled {
+        standing_still(
+            "
+map: {dimensions: [7, 1], obstacles: [[6, 0]]}
+agents:
+  - {name: attacker, start: [3, 0], goal: [3, 0]}
+  - {name: near, start: [1, 0], goal: [1, 0]}
+  - {name: far, start: [0, 0], goal: [0, 0]}
+",
+        )
+    }
+
+    // every agent of `instance` waiting at its start
+    fn standing_still(instance: &str) -> Compiled {
+        let instance: MapfInstance = serde_yaml::from_str(instance).unwrap();
+        let makespan = 6;
+        let solution = MapfSolution {
+            statistics: Statistics {
+                cost: 0,
+                makespan,
+                runtime: 0.0,
+                highLevelExpanded: 0,
+                lowLevelExpanded: 0,
+            },
+            schedule: instance
+                .agents
+                .iter()
+                .map(|agent| {
+                    let path = (0..makespan + 2).map(|t| agent.start.as_time(t)).collect();
+                    (agent.name.clone(), path)
+                })
+                .collect(),
+        };
+        let announcements = compute_kahead_announcements(&instance.agents, 2, makespan);
+        Compiled::new(&instance, &solution, &announcements)
+    }
+
+    #[test]
+    fn responders_that_cannot_catch_up_do_not_stop_the_attacker() {
+        let compiled = compiled();
+        let safe = Coordinate { x: 6, y: 0 };
+        // east one cell per step, into the target at 3
+        let trajectory: Vec<_> = (0..4)
+            .map(|t| TimedCoordinate {
+                x: 3 + t as u16,
+                y: 0,
+                t,
+            })
+            .collect();
+        let response = |strategy| Response {
+            responders: 1,
+            security_robot: None,
+            strategy,
+        };
+
+        // `near` is always a step short of the attacker running away from it
+        let late = response(ResponseStrategy::Intercept).contain(
+            &compiled,
+            0,
+            &safe,
+            &trajectory,
+            Some(0),
+            Some(3),
+        );
+        assert_eq!(late.responders, vec!["near".to_string()]);
+        assert_eq!(late.contained_at, None);
+        assert!(!late.stopped());
+
+        // but next to it at 1 if the attacker waits a step
+        let waiting: Vec<_> = [3, 3, 4, 5, 6]
+            .iter()
+            .enumerate()
+            .map(|(t, &x)| TimedCoordinate { x, y: 0, t })
+            .collect();
+        let caught = response(ResponseStrategy::Intercept).contain(
+            &compiled,
+            0,
+            &safe,
+            &waiting,
+            Some(0),
+            Some(4),
+        );
+        assert_eq!(caught.contained_at, Some(1));
+        assert!(caught.stopped());
+
+        // holding (5, 0) next to the target takes `near` 4 steps, one more than the attacker
+        let cordon = response(ResponseStrategy::Cordon).contain(
+            &compiled,
+            0,
+            &safe,
+            &trajectory,
+            Some(0),
+            Some(3),
+        );
+        assert_eq!(cordon.contained_at, Some(4));
+        assert!(!cordon.stopped());
+
+        // the attacker cannot respond to itself
+        let robot = Response {
+            responders: 1,
+            security_robot: Some("attacker".to_string()),
+            strategy: ResponseStrategy::Intercept,
+        };
+        let alone = robot.contain(&compiled, 0, &safe, &trajectory, Some(0), Some(3));
+        assert!(alone.responders.is_empty());
+        assert!(!alone.stopped());
+
+        let undetected = response(ResponseStrategy::Intercept).contain(
+            &compiled,
+            0,
+            &safe,
+            &trajectory,
+            None,
+            Some(3),
+        );
+        assert_eq!(undetected.contained_at, None);
+    }
+
+    #[test]
+    fn a_cordon_holds_every_free_cell_next_to_the_target() {
+        // the target at (2, 0) has three free cells next to it, (1, 0), (3, 0) and (2, 1)
+        let compiled = standing_still(
+            "
+map: {dimensions: [5, 2], obstacles: [[2, 0]]}
+agents:
+  - {name: attacker, start: [0, 1], goal: [0, 1]}
+  - {name: west, start: [0, 0], goal: [0, 0]}
+  - {name: south, start: [4, 1], goal: [4, 1]}
+  - {name: east, start: [4, 0], goal: [4, 0]}
+",
+        );
+        let safe = Coordinate { x: 2, y: 0 };
+        let trajectory: Vec<_> = (0..6).map(|t| TimedCoordinate { x: 0, y: 1, t }).collect();
+        let cordon = |responders| Response {
+            responders,
+            security_robot: None,
+            strategy: ResponseStrategy::Cordon,
+        };
+
+        // two responders leave a cell open however soon they arrive
+        let short = cordon(2).contain(&compiled, 0, &safe, &trajectory, Some(0), None);
+        assert_eq!(
+            short.responders,
+            vec!["west".to_string(), "south".to_string()]
+        );
+        assert_eq!(short.contained_at, None);
+
+        // `west` and `east` are next to the target after one step, but `south` needs two to the
+        // cell left to it
+        let enough = cordon(3).contain(&compiled, 0, &safe, &trajectory, Some(0), None);
+        assert_eq!(enough.contained_at, Some(2));
+        assert!(enough.stopped());
+    }
+}
@@ -0,0 +1,396 @@
+//! Imperfect co-observations for the bold analysis: defenders that overlook deviations, raise
+//! false alarms or lie about what they saw, estimated with seeded Monte Carlo runs, see
+//! [`SensingModel`].
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::compiled::AgentId;
+use crate::inputs::Coordinate;
+
+/// How reliably defenders notice and report what they co-observe. Without one every deviation a
+/// defender is placed to notice is detected and nothing else is.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SensingModel {
+    /// Chance a defender notices a deviation it is placed to notice, independently per
+    /// observation.
+    pub detection_probability: f64,
+    /// Chance a defender raises an alarm when it sees the attacker where the plan has it,
+    /// independently per observation.
+    pub false_positive_rate: f64,
+    /// Defenders that report as `liar_behavior` says rather than what they saw, drawn anew for
+    /// every run. Capped at the number of defenders.
+    #[serde(default)]
+    pub liars: usize,
+    #[serde(default)]
+    pub liar_behavior: LiarBehavior,
+    /// Distinct defenders that must accuse the attacker before an alarm is raised.
+    #[serde(default = "one")]
+    pub quorum: usize,
+    /// Monte Carlo runs per attempt.
+    pub runs: usize,
+    /// Seeds the runs, so reruns and resumed runs draw the same alarms.
+    pub seed: u64,
+    /// Of the intervals around the estimated rates.
+    pub confidence: f64,
+}
+
+fn one() -> usize {
+    1
+}
+
+/// What a lying defender reports whenever it co-observes the attacker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiarBehavior {
+    /// Nothing, even when it notices a deviation.
+    #[default]
+    Omit,
+    /// An accusation, whether or not the attacker deviates.
+    Fabricate,
+    /// That the attacker is where the plan has it, outweighing one accusation at the same step.
+    Vouch,
+}
+
+impl LiarBehavior {
+    /// As written in result files and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            LiarBehavior::Omit => "omit",
+            LiarBehavior::Fabricate => "fabricate",
+            LiarBehavior::Vouch => "vouch",
+        }
+    }
+}
+
+/// What the defenders could have noticed during one bold attempt, recorded once since the bold
+/// attacker's walk doesn't depend on the alarms.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DetectionChances {
+    /// A collision, or an unexpected observation no defender was placed to make, detected
+    /// whatever the sensors and reports as in the analysis without a model.
+    pub certain: bool,
+    /// Every defender, the candidates for liars.
+    pub defenders: Vec<AgentId>,
+    /// The steps some defender co-observed the attacker, in time order.
+    pub steps: Vec<Witnesses>,
+}
+
+/// The defenders co-observing the attacker at one step.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Witnesses {
+    /// Placed to notice a deviation, through an unexpected or a missed observation.
+    pub deviation: Vec<AgentId>,
+    /// Seeing the attacker where the plan has it.
+    pub conforming: Vec<AgentId>,
+}
+
+impl Witnesses {
+    pub fn is_empty(&self) -> bool {
+        self.deviation.is_empty() && self.conforming.is_empty()
+    }
+}
+
+/// How many of an attempt's Monte Carlo runs raised an alarm.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SensedDetections {
+    pub runs: usize,
+    pub detected: usize,
+}
+
+impl SensingModel {
+    /// Runs the model `runs` times over `chances`. Every attempt draws from its own stream of
+    /// the seed, so results don't depend on the order attempts run in.
+    pub fn sample(
+        &self,
+        chances: &DetectionChances,
+        attacker: AgentId,
+        safe: &Coordinate,
+    ) -> SensedDetections {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(u64::from(attacker) << 32 | u64::from(safe.y) << 16 | u64::from(safe.x));
+        let detected = (0..self.runs)
+            .filter(|_| chances.certain || self.alarm(chances, &mut rng))
+            .count();
+        SensedDetections {
+            runs: self.runs,
+            detected,
+        }
+    }
+
+    // whether one run gathers a quorum of accusers, counting a step's accusations only if they
+    // outnumber the defenders vouching for the attacker at that step
+    fn alarm(&self, chances: &DetectionChances, rng: &mut ChaCha8Rng) -> bool {
+        let liars: HashSet<AgentId> = chances
+            .defenders
+            .choose_multiple(rng, self.liars)
+            .copied()
+            .collect();
+        let mut accusers: HashSet<AgentId> = HashSet::new();
+        for step in &chances.steps {
+            let mut accusing: HashSet<AgentId> = HashSet::new();
+            let mut vouching = 0;
+            let reports = step
+                .deviation
+                .iter()
+                .map(|&defender| (defender, self.detection_probability))
+                .chain(
+                    step.conforming
+                        .iter()
+                        .map(|&defender| (defender, self.false_positive_rate)),
+                );
+            for (defender, accuses) in reports {
+                if !liars.contains(&defender) {
+                    if rng.gen_bool(accuses) {
+                        accusing.insert(defender);
+                    }
+                    continue;
+                }
+                match self.liar_behavior {
+                    LiarBehavior::Omit => {}
+                    LiarBehavior::Fabricate => {
+                        accusing.insert(defender);
+                    }
+                    LiarBehavior::Vouch => vouching += 1,
+                }
+            }
+            if accusing.len() > vouching {
+                accusers.extend(accusing);
+                if accusers.len() >= self.quorum {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// A rate estimated from Monte Carlo runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub rate: f64,
+    /// Wilson score interval at the model's confidence.
+    pub interval: (f64, f64),
+    pub runs: usize,
+}
+
+impl Estimate {
+    /// `None` without any runs to estimate from.
+    pub fn of(successes: usize, runs: usize, confidence: f64) -> Option<Estimate> {
+        if runs == 0 {
+            return None;
+        }
+        Some(Estimate {
+            rate: successes as f64 / runs as f64,
+            interval: wilson_interval(successes, runs, confidence),
+            runs,
+        })
+    }
+}
+
+/// Wilson score interval of a binomial proportion, which unlike the normal approximation stays
+/// within [0, 1] and doesn't collapse when no or every trial succeeds.
+pub fn wilson_interval(successes: usize, trials: usize, confidence: f64) -> (f64, f64) {
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z = normal_quantile(1.0 - (1.0 - confidence) / 2.0);
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+// inverse of the standard normal CDF, Acklam's rational approximation (relative error below
+// 1.2e-9)
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.38357751867269e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiments::{run_bold_attempts, BoldDefense};
+    use crate::inputs::MapfInstance;
+    use crate::planner::plan_prioritized;
+    use crate::utils::{compute_kahead_announcements, extend_stay_in_place};
+
+    fn model(detection_probability: f64, false_positive_rate: f64) -> SensingModel {
+        SensingModel {
+            detection_probability,
+            false_positive_rate,
+            liars: 0,
+            liar_behavior: LiarBehavior::Omit,
+            quorum: 1,
+            runs: 4000,
+            seed: 7,
+            confidence: 0.95,
+        }
+    }
+
+    // defenders 1 to 3, `steps` given as (deviation, conforming) witnesses
+    fn chances(steps: &[(&[AgentId], &[AgentId])]) -> DetectionChances {
+        DetectionChances {
+            certain: false,
+            defenders: vec![1, 2, 3],
+            steps: steps
+                .iter()
+                .map(|(deviation, conforming)| Witnesses {
+                    deviation: deviation.to_vec(),
+                    conforming: conforming.to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn wilson_interval_matches_reference_values() {
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-6);
+        let (lo, hi) = wilson_interval(0, 10, 0.95);
+        assert_eq!(lo, 0.0);
+        assert!((hi - 0.2775).abs() < 1e-4);
+        let (lo, hi) = wilson_interval(5, 10, 0.95);
+        assert!((lo - 0.2366).abs() < 1e-4 && (hi - 0.7634).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sampled_detections_follow_the_model() {
+        let safe = Coordinate { x: 3, y: 4 };
+        let deviating = chances(&[(&[1], &[]), (&[2], &[])]);
+        // perfect sensing is the deterministic analysis
+        assert_eq!(model(1.0, 0.0).sample(&deviating, 0, &safe).detected, 4000);
+        assert_eq!(model(0.0, 0.0).sample(&deviating, 0, &safe).detected, 0);
+        let certain = DetectionChances {
+            certain: true,
+            ..deviating.clone()
+        };
+        assert_eq!(model(0.0, 0.0).sample(&certain, 0, &safe).detected, 4000);
+
+        // two chances at a half, so three quarters of the runs detect
+        let sensed = model(0.5, 0.0).sample(&deviating, 0, &safe);
+        let estimate = Estimate::of(sensed.detected, sensed.runs, 0.99).unwrap();
+        assert!(estimate.interval.0 < 0.75 && 0.75 < estimate.interval.1);
+        // seeded per attempt
+        assert_eq!(sensed, model(0.5, 0.0).sample(&deviating, 0, &safe));
+
+        let conforming = chances(&[(&[], &[1])]);
+        let sensed = model(1.0, 0.1).sample(&conforming, 0, &safe);
+        let estimate = Estimate::of(sensed.detected, sensed.runs, 0.99).unwrap();
+        assert!(estimate.interval.0 < 0.1 && 0.1 < estimate.interval.1);
+    }
+
+    #[test]
+    fn every_bold_attempt_gets_the_same_sensed_runs_on_a_rerun() {
+        let run = || {
+            let instance: MapfInstance = serde_yaml::from_str(
+                "
+map: {dimensions: [10, 8], obstacles: [[4, 3], [5, 3], [4, 4], [7, 5]]}
+agents:
+  - {name: agent0, start: [1, 1], goal: [8, 6]}
+  - {name: agent1, start: [8, 1], goal: [1, 6]}
+  - {name: agent2, start: [1, 5], goal: [8, 2]}
+",
+            )
+            .unwrap();
+            let mut solution = plan_prioritized(&instance).unwrap();
+            extend_stay_in_place(&mut solution);
+            let announcements =
+                compute_kahead_announcements(&instance.agents, 3, solution.statistics.makespan);
+            let sensing = model(0.9, 0.01);
+            let defense = BoldDefense {
+                mitigation: true,
+                sensing: Some(&sensing),
+                response: None,
+            };
+            run_bold_attempts(instance, solution, announcements, None, &defense)
+        };
+        let res = run();
+        assert!(res
+            .attempts
+            .iter()
+            .all(|attempt| attempt.sensed.is_some_and(|sensed| sensed.runs == 4000)));
+        assert_eq!(res, run());
+    }
+
+    #[test]
+    fn liars_and_quorums_decide_the_alarm() {
+        let safe = Coordinate { x: 3, y: 4 };
+        let detected = |liars, liar_behavior, quorum, chances: &DetectionChances| {
+            let model = SensingModel {
+                liars,
+                liar_behavior,
+                quorum,
+                ..model(1.0, 0.0)
+            };
+            model.sample(chances, 0, &safe).detected
+        };
+        // two accusers over the attempt
+        let deviating = chances(&[(&[1], &[]), (&[2], &[3])]);
+        assert_eq!(detected(0, LiarBehavior::Omit, 2, &deviating), 4000);
+        assert_eq!(detected(0, LiarBehavior::Omit, 3, &deviating), 0);
+        assert_eq!(detected(3, LiarBehavior::Omit, 1, &deviating), 0);
+        // the conforming witness makes up the third accusation
+        assert_eq!(detected(3, LiarBehavior::Fabricate, 3, &deviating), 4000);
+        // a single liar among the two silences one of them, so only some runs reach a quorum
+        let omitted = detected(1, LiarBehavior::Omit, 2, &deviating);
+        assert!(0 < omitted && omitted < 4000);
+        // the liars of each run are seeded too
+        assert_eq!(omitted, detected(1, LiarBehavior::Omit, 2, &deviating));
+
+        // one of the two witnesses always lies, and vouching outweighs the other
+        let together = chances(&[(&[1, 2], &[])]);
+        assert_eq!(detected(2, LiarBehavior::Vouch, 1, &together), 0);
+        let mut pair = together.clone();
+        pair.defenders = vec![1, 2];
+        assert_eq!(detected(1, LiarBehavior::Vouch, 1, &pair), 0);
+        assert_eq!(detected(1, LiarBehavior::Omit, 1, &pair), 4000);
+        // only fabricating liars accuse an attacker following its plan
+        let conforming = chances(&[(&[], &[1, 2, 3])]);
+        assert_eq!(detected(2, LiarBehavior::Vouch, 1, &conforming), 0);
+        assert_eq!(detected(1, LiarBehavior::Fabricate, 1, &conforming), 4000);
+    }
+}
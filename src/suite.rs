@@ -0,0 +1,534 @@
+//! Batch runs of `analyze-attackers` described by a manifest, see [`run_suite`].
+//!
+//! Every job runs as its own `analyze-attackers` process so it can be killed when it exceeds
+//! the manifest's timeout; the jobs themselves are scheduled on a rayon pool.
+
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::loader::{read_yaml, write_yaml};
+use crate::plotting::ResultKind;
+use crate::sensing::LiarBehavior;
+
+/// The instances to run on: a list of names, or the path of a file with one name per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Instances {
+    Names(Vec<String>),
+    File(String),
+}
+
+/// Announcement strategies a suite can sweep; custom announcements need a file per instance
+/// and aren't supported.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    Kahead,
+    Kgrouped,
+    Robust,
+}
+
+impl Strategy {
+    fn name(self) -> &'static str {
+        match self {
+            Strategy::Kahead => "kahead",
+            Strategy::Kgrouped => "kgrouped",
+            Strategy::Robust => "robust",
+        }
+    }
+
+    fn takes_lookahead(self) -> bool {
+        self != Strategy::Robust
+    }
+}
+
+/// Values of k: `{from, to, step}` like `seq from step to`, or an explicit list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Lookaheads {
+    Range {
+        from: usize,
+        to: usize,
+        #[serde(default = "one")]
+        step: usize,
+    },
+    List(Vec<usize>),
+}
+
+fn one() -> usize {
+    1
+}
+
+impl Lookaheads {
+    fn values(&self) -> Vec<usize> {
+        match self {
+            Lookaheads::Range { from, to, step } => (*from..=*to).step_by((*step).max(1)).collect(),
+            Lookaheads::List(values) => values.clone(),
+        }
+    }
+}
+
+/// One block of the manifest, expanded to every combination of its attackers, strategies,
+/// lookaheads (for strategies that take one), mitigation flags (for bold attackers) and numbers
+/// of lying witnesses (for bold attackers with mitigation).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sweep {
+    pub attackers: Vec<ResultKind>,
+    pub strategies: Vec<Strategy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lookahead: Option<Lookaheads>,
+    #[serde(default = "mitigation_on")]
+    pub mitigation: Vec<bool>,
+    /// Pass `--skip-large`, skipping lookaheads beyond the makespan.
+    #[serde(default)]
+    pub skip_large: bool,
+    /// Overrides the manifest's timeout for these jobs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Passed as `--attempt-timeout` to cautious jobs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt_timeout: Option<u64>,
+    /// Passed as `--liars` to bold jobs with mitigation, one job each.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub liars: Vec<usize>,
+    /// Passed as `--liar-behavior` along with `liars`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liar_behavior: Option<LiarBehavior>,
+    /// Passed as `--quorum` along with `liars`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quorum: Option<usize>,
+}
+
+fn mitigation_on() -> Vec<bool> {
+    vec![true]
+}
+
+/// A suite of experiments. Relative paths are relative to the manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Directory of `<instance>.yaml` maps.
+    pub maps: String,
+    /// Directory of `<instance>.yaml` plans.
+    pub plans: String,
+    pub instances: Instances,
+    /// Directory the results and `index.yaml` are written to.
+    pub outputs: String,
+    /// Directory for each job's output, discarded if not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs: Option<String>,
+    /// Seconds before a job is killed, unless its sweep says otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Jobs run at once; each analysis is parallel already.
+    #[serde(default = "one")]
+    pub parallel: usize,
+    pub sweeps: Vec<Sweep>,
+}
+
+/// One `analyze-attackers` run of a suite.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub instance: String,
+    pub attacker: ResultKind,
+    pub strategy: Strategy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lookahead: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mitigation: Option<bool>,
+    pub skip_large: bool,
+    /// Seconds before the job is killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Seconds before a cautious attempt is given up as unknown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt_timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liars: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liar_behavior: Option<LiarBehavior>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quorum: Option<usize>,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Ran and wrote its result.
+    Done,
+    /// The result was there from an earlier run.
+    Existing,
+    /// Skipped by `--skip-large`.
+    Skipped,
+    Failed,
+    Timeout,
+}
+
+/// What happened to one job, as listed in `index.yaml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobReport {
+    #[serde(flatten)]
+    pub job: Job,
+    pub status: JobStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Seconds, zero for existing results.
+    pub wall_time: f64,
+}
+
+/// Every job of a suite, in manifest order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuiteIndex {
+    pub jobs: Vec<JobReport>,
+}
+
+impl Manifest {
+    /// Expands the sweeps for every instance. Output names follow the shell scripts this
+    /// replaces, `<instance>_<bold|no-mitigation|cautious>_<strategy>[_<k>].yaml`, with
+    /// `_<liar behavior><liars>[_q<quorum>]` before the extension for lying witnesses. A job
+    /// whose output an earlier sweep already writes is left out, so no two jobs write, and
+    /// checkpoint, the same file.
+    pub fn jobs(&self, instances: &[String]) -> Vec<Job> {
+        let mut jobs = Vec::new();
+        let mut outputs = HashSet::new();
+        for sweep in &self.sweeps {
+            for instance in instances {
+                for &attacker in &sweep.attackers {
+                    for &strategy in &sweep.strategies {
+                        let lookaheads: Vec<Option<usize>> = match &sweep.lookahead {
+                            Some(lookaheads) if strategy.takes_lookahead() => {
+                                lookaheads.values().into_iter().map(Some).collect()
+                            }
+                            _ => vec![None],
+                        };
+                        let mitigations: Vec<Option<bool>> = match attacker {
+                            ResultKind::Bold => {
+                                sweep.mitigation.iter().copied().map(Some).collect()
+                            }
+                            ResultKind::Cautious => vec![None],
+                        };
+                        for &lookahead in &lookaheads {
+                            for &mitigation in &mitigations {
+                                let kind = match (attacker, mitigation) {
+                                    (ResultKind::Cautious, _) => "cautious",
+                                    (_, Some(false)) => "no-mitigation",
+                                    _ => "bold",
+                                };
+                                let k = lookahead.map_or(String::new(), |k| format!("_{:03}", k));
+                                let liars: Vec<Option<usize>> = match mitigation {
+                                    Some(true) if !sweep.liars.is_empty() => {
+                                        sweep.liars.iter().copied().map(Some).collect()
+                                    }
+                                    _ => vec![None],
+                                };
+                                for &liar_count in &liars {
+                                    let witnesses = liar_count.map_or(String::new(), |n| {
+                                        let behavior = sweep.liar_behavior.unwrap_or_default();
+                                        let quorum = sweep
+                                            .quorum
+                                            .map_or(String::new(), |q| format!("_q{}", q));
+                                        format!("_{}{:02}{}", behavior.name(), n, quorum)
+                                    });
+                                    let output = format!(
+                                        "{}_{}_{}{}{}.yaml",
+                                        instance,
+                                        kind,
+                                        strategy.name(),
+                                        k,
+                                        witnesses
+                                    );
+                                    if !outputs.insert(output.clone()) {
+                                        continue;
+                                    }
+                                    jobs.push(Job {
+                                        instance: instance.clone(),
+                                        attacker,
+                                        strategy,
+                                        lookahead,
+                                        mitigation,
+                                        skip_large: sweep.skip_large,
+                                        timeout: sweep.timeout.or(self.timeout),
+                                        attempt_timeout: match attacker {
+                                            ResultKind::Cautious => sweep.attempt_timeout,
+                                            ResultKind::Bold => None,
+                                        },
+                                        liars: liar_count,
+                                        liar_behavior: liar_count.and(sweep.liar_behavior),
+                                        quorum: liar_count.and(sweep.quorum),
+                                        output,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        jobs
+    }
+}
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> Error + '_ {
+    move |source| Error::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
+/// Runs every job of the manifest at `manifest_path` with the `announcenet` binary at `exe`,
+/// skipping results already present, and writes `index.yaml` to the outputs directory.
+/// Empty results, left behind by killed runs, are run again.
+pub fn run_suite(manifest_path: &str, exe: &Path) -> Result<SuiteIndex> {
+    let manifest: Manifest = read_yaml(manifest_path)?;
+    let base = Path::new(manifest_path)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
+    let instances = match &manifest.instances {
+        Instances::Names(names) => names.clone(),
+        Instances::File(path) => {
+            let path = base.join(path);
+            fs::read_to_string(&path)
+                .map_err(io_error(&path))?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        }
+    };
+    let outputs = base.join(&manifest.outputs);
+    fs::create_dir_all(&outputs).map_err(io_error(&outputs))?;
+    let logs = manifest.logs.as_ref().map(|logs| base.join(logs));
+    if let Some(logs) = &logs {
+        fs::create_dir_all(logs).map_err(io_error(logs))?;
+    }
+    let jobs = manifest.jobs(&instances);
+    let runner = Runner {
+        exe,
+        maps: base.join(&manifest.maps),
+        plans: base.join(&manifest.plans),
+        outputs: outputs.clone(),
+        logs,
+    };
+
+    let pb = ProgressBar::new(jobs.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("[{eta:>5}->{elapsed:>5}] [{wide_bar}] {pos:>7}/{len:>7}")
+            .progress_chars("=> "),
+    );
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(manifest.parallel.max(1))
+        .build()
+        .expect("failed to start the job pool");
+    let reports: Vec<JobReport> = pool.install(|| {
+        jobs.into_par_iter()
+            .progress_with(pb.clone())
+            .map(|job| runner.run(job, &pb))
+            .collect::<Result<_>>()
+    })?;
+    pb.finish();
+
+    let index = SuiteIndex { jobs: reports };
+    write_yaml(&outputs.join("index.yaml").display().to_string(), &index)?;
+    Ok(index)
+}
+
+struct Runner<'a> {
+    exe: &'a Path,
+    maps: PathBuf,
+    plans: PathBuf,
+    outputs: PathBuf,
+    logs: Option<PathBuf>,
+}
+
+impl<'a> Runner<'a> {
+    fn run(&self, job: Job, pb: &ProgressBar) -> Result<JobReport> {
+        let output = self.outputs.join(&job.output);
+        let report = |status, exit_code, started: Option<Instant>| JobReport {
+            job: job.clone(),
+            status,
+            exit_code,
+            wall_time: started.map_or(0.0, |started| started.elapsed().as_secs_f64()),
+        };
+        match fs::metadata(&output) {
+            Ok(metadata) if metadata.len() > 0 => {
+                return Ok(report(JobStatus::Existing, None, None))
+            }
+            Ok(_) => fs::remove_file(&output).map_err(io_error(&output))?,
+            Err(_) => {}
+        }
+
+        let mut command = Command::new(self.exe);
+        command
+            .arg("analyze-attackers")
+            .arg("-t")
+            .arg(match job.attacker {
+                ResultKind::Bold => "bold",
+                ResultKind::Cautious => "cautious",
+            })
+            .arg("-a")
+            .arg(job.strategy.name())
+            .arg("-m")
+            .arg(self.maps.join(format!("{}.yaml", job.instance)))
+            .arg("-s")
+            .arg(self.plans.join(format!("{}.yaml", job.instance)))
+            .arg("-o")
+            .arg(&output)
+            // picks up the attempts of an earlier run that failed or timed out
+            .arg("-r");
+        if let Some(k) = job.lookahead {
+            command.arg("-k").arg(k.to_string());
+        }
+        if job.skip_large {
+            command.arg("-x");
+        }
+        if job.mitigation == Some(false) {
+            command.arg("-n");
+        }
+        if let Some(seconds) = job.attempt_timeout {
+            command.arg("--attempt-timeout").arg(seconds.to_string());
+        }
+        if let Some(liars) = job.liars {
+            command.arg("--liars").arg(liars.to_string());
+        }
+        if let Some(behavior) = job.liar_behavior {
+            command.arg("--liar-behavior").arg(behavior.name());
+        }
+        if let Some(quorum) = job.quorum {
+            command.arg("--quorum").arg(quorum.to_string());
+        }
+        match &self.logs {
+            Some(logs) => {
+                let path = logs.join(Path::new(&job.output).with_extension("log"));
+                let log = File::create(&path).map_err(io_error(&path))?;
+                let err = log.try_clone().map_err(io_error(&path))?;
+                command.stdout(log).stderr(err);
+            }
+            None => {
+                command.stdout(Stdio::null()).stderr(Stdio::null());
+            }
+        }
+
+        let started = Instant::now();
+        let mut child = command.spawn().map_err(io_error(self.exe))?;
+        let exit = loop {
+            if let Some(exit) = child.try_wait().map_err(io_error(self.exe))? {
+                break Some(exit);
+            }
+            if job
+                .timeout
+                .is_some_and(|timeout| started.elapsed() > Duration::from_secs(timeout))
+            {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            thread::sleep(Duration::from_millis(100));
+        };
+        let status = match exit {
+            Some(status) if status.success() => {
+                if output.exists() {
+                    JobStatus::Done
+                } else {
+                    JobStatus::Skipped
+                }
+            }
+            Some(_) => JobStatus::Failed,
+            None => JobStatus::Timeout,
+        };
+        if matches!(status, JobStatus::Failed | JobStatus::Timeout) {
+            pb.println(format!("{}: {:?}", job.output, status).to_lowercase());
+            // leave no empty result behind, so the next run retries it, resuming from the
+            // checkpoint next to it
+            if fs::metadata(&output).is_ok_and(|metadata| metadata.len() == 0) {
+                let _ = fs::remove_file(&output);
+            }
+        }
+        Ok(report(
+            status,
+            exit.and_then(|exit| exit.code()),
+            Some(started),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweeps_expand_like_the_shell_scripts() {
+        let manifest: Manifest = serde_yaml::from_str(
+            "
+maps: inputs/maps
+plans: inputs/plans
+instances: [a, b]
+outputs: outputs
+sweeps:
+  - attackers: [bold]
+    strategies: [kahead, robust]
+    lookahead: { from: 1, to: 9, step: 4 }
+    mitigation: [true, false]
+    skip_large: true
+  - attackers: [cautious]
+    strategies: [kgrouped]
+    lookahead: [2]
+    timeout: 600
+    attempt_timeout: 30
+  - attackers: [bold]
+    strategies: [robust]
+    mitigation: [true, false]
+    skip_large: true
+    liars: [0, 2]
+    liar_behavior: vouch
+    quorum: 2
+timeout: 60
+",
+        )
+        .unwrap();
+        let jobs = manifest.jobs(&["a".to_string(), "b".to_string()]);
+        let outputs: Vec<&str> = jobs
+            .iter()
+            .filter(|job| job.instance == "a")
+            .map(|job| job.output.as_str())
+            .collect();
+        assert_eq!(
+            outputs,
+            vec![
+                "a_bold_kahead_001.yaml",
+                "a_no-mitigation_kahead_001.yaml",
+                "a_bold_kahead_005.yaml",
+                "a_no-mitigation_kahead_005.yaml",
+                "a_bold_kahead_009.yaml",
+                "a_no-mitigation_kahead_009.yaml",
+                "a_bold_robust.yaml",
+                "a_no-mitigation_robust.yaml",
+                "a_cautious_kgrouped_002.yaml",
+                "a_bold_robust_vouch00_q2.yaml",
+                "a_bold_robust_vouch02_q2.yaml",
+            ]
+        );
+        // the first sweep already writes a_no-mitigation_robust.yaml
+        assert_eq!(jobs.len(), 22);
+        assert!(jobs
+            .iter()
+            .all(|job| job.skip_large == (job.attacker == ResultKind::Bold)));
+        assert_eq!(jobs[0].timeout, Some(60));
+        assert_eq!(jobs[17].timeout, Some(600));
+        assert_eq!(jobs[17].attempt_timeout, Some(30));
+        assert_eq!(jobs[0].attempt_timeout, None);
+        assert_eq!(jobs[19].liars, Some(2));
+        assert_eq!(jobs[19].liar_behavior, Some(LiarBehavior::Vouch));
+        assert_eq!(jobs[7].quorum, None);
+        assert_eq!(manifest.parallel, 1);
+    }
+}
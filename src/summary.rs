@@ -0,0 +1,164 @@
+//! Flat tables of experiment results for analysis elsewhere, see [`summarize`].
+
+use std::fs::File;
+use std::io::Write;
+
+use crate::error::{Error, Result};
+use crate::loader::touch;
+use crate::plotting::{read_stdin_records, Level, Record, ResultKind};
+
+/// Reads result paths from stdin, like [`generate_plots`](crate::generate_plots), and writes one
+/// row per experiment or attempt to `output`: every field of the records the plots see, i.e. the
+/// metrics, the derived rates and the provenance header. Written as CSV unless `output` ends in
+/// `.parquet`, which needs the `parquet` feature.
+pub fn summarize(results: ResultKind, level: Level, output: &str, force: bool) -> Result<()> {
+    let parquet = output.ends_with(".parquet");
+    if parquet && !cfg!(feature = "parquet") {
+        return Err(Error::Export {
+            path: output.to_string(),
+            reason: "built without the parquet feature".to_string(),
+        });
+    }
+    touch(output, force)?;
+    let records = read_stdin_records(results, level)?;
+    let columns = columns(&records);
+    let file = File::create(output).map_err(|source| Error::Io {
+        path: output.to_string(),
+        source,
+    })?;
+    let export_error = |why: String| Error::Export {
+        path: output.to_string(),
+        reason: why,
+    };
+    if parquet {
+        #[cfg(feature = "parquet")]
+        write_parquet(file, &columns, &records).map_err(export_error)?;
+    } else {
+        write_csv(file, &columns, &records).map_err(|why| export_error(why.to_string()))?;
+    }
+    println!(
+        "wrote {} rows of {} columns to {}",
+        records.len(),
+        columns.len(),
+        output
+    );
+    Ok(())
+}
+
+/// Every field of any record: `result` first, the rest by name.
+pub fn columns(records: &[Record]) -> Vec<String> {
+    let mut columns: Vec<String> = records
+        .iter()
+        .flat_map(|record| record.keys())
+        .filter(|field| *field != "result")
+        .cloned()
+        .collect();
+    columns.sort();
+    columns.dedup();
+    columns.insert(0, "result".to_string());
+    columns
+}
+
+/// Writes `records` as CSV with a header row, leaving fields a record lacks empty.
+pub fn write_csv<W: Write>(out: W, columns: &[String], records: &[Record]) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(columns)?;
+    for record in records {
+        writer.write_record(columns.iter().map(|column| {
+            record
+                .get(column)
+                .map_or_else(String::new, |value| value.to_string())
+        }))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// columns holding only numbers become nullable doubles, the others nullable strings
+#[cfg(feature = "parquet")]
+fn write_parquet(
+    file: File,
+    columns: &[String],
+    records: &[Record],
+) -> std::result::Result<(), String> {
+    use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+    for column in columns {
+        let values: Vec<_> = records.iter().map(|record| record.get(column)).collect();
+        let numbers: Option<Vec<Option<f64>>> = values
+            .iter()
+            .map(|value| match value {
+                Some(value) => value.number().map(Some),
+                None => Some(None),
+            })
+            .collect();
+        match numbers {
+            Some(numbers) => {
+                fields.push(Field::new(column, DataType::Float64, true));
+                arrays.push(Arc::new(Float64Array::from(numbers)));
+            }
+            None => {
+                fields.push(Field::new(column, DataType::Utf8, true));
+                arrays.push(Arc::new(StringArray::from(
+                    values
+                        .iter()
+                        .map(|value| value.map(|value| value.to_string()))
+                        .collect::<Vec<_>>(),
+                )));
+            }
+        }
+    }
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(|why| why.to_string())?;
+    let mut writer = ArrowWriter::try_new(file, schema, None).map_err(|why| why.to_string())?;
+    writer.write(&batch).map_err(|why| why.to_string())?;
+    writer.close().map_err(|why| why.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plotting::Value;
+
+    #[test]
+    fn csv_has_every_column_and_blanks_for_missing_fields() {
+        let record = |fields: &[(&str, Value)]| -> Record {
+            fields
+                .iter()
+                .map(|(field, value)| (field.to_string(), value.clone()))
+                .collect()
+        };
+        let records = vec![
+            record(&[
+                ("result", Value::Text("a.yaml".to_string())),
+                ("dangerous", Value::Number(1.0)),
+                ("miss", Value::Number(0.0)),
+            ]),
+            record(&[
+                ("result", Value::Text("b, c.yaml".to_string())),
+                ("dangerous", Value::Number(0.0)),
+                ("false_alarm", Value::Number(1.0)),
+                ("strategy", Value::Text("kahead".to_string())),
+            ]),
+        ];
+        let columns = columns(&records);
+        assert_eq!(
+            columns,
+            vec!["result", "dangerous", "false_alarm", "miss", "strategy"]
+        );
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &columns, &records).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "result,dangerous,false_alarm,miss,strategy\n\
+             a.yaml,1,,0,\n\
+             \"b, c.yaml\",0,1,,kahead\n"
+        );
+    }
+}